@@ -0,0 +1,67 @@
+// Character classes of the Lox lexical grammar.
+// Only ASCII letters and digits make up numbers and identifiers: other Unicode
+// letters and digits, such as 'é' or '٣', are unexpected characters.
+
+pub fn is_digit(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
+pub fn is_alpha(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+pub fn is_alpha_numeric(c: char) -> bool {
+    is_digit(c) || is_alpha(c)
+}
+
+// Whitespace between tokens, once line endings are normalized to '\n'.
+pub fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_chars() -> impl Iterator<Item = char> {
+        (0..=u32::from(char::MAX)).filter_map(char::from_u32)
+    }
+
+    #[test]
+    fn test_bounds() {
+        for c in ['0', '9'] {
+            assert!(is_digit(c));
+        }
+        for c in ['a', 'z', 'A', 'Z', '_'] {
+            assert!(is_alpha(c));
+        }
+        for c in ['/', ':', '`', '{', '@', '['] {
+            assert!(!is_alpha_numeric(c), "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn test_every_char() {
+        for c in all_chars() {
+            assert_eq!(c.is_ascii_digit(), is_digit(c), "{:?}", c);
+            assert_eq!(c.is_ascii_alphabetic() || c == '_', is_alpha(c), "{:?}", c);
+            assert_eq!(
+                c.is_ascii_alphanumeric() || c == '_',
+                is_alpha_numeric(c),
+                "{:?}",
+                c
+            );
+            if is_whitespace(c) {
+                assert!(c.is_ascii_whitespace(), "{:?}", c);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unicode_is_not_alpha_numeric() {
+        for c in ['é', 'ß', 'λ', '٣', '５', '\u{a0}'] {
+            assert!(!is_alpha_numeric(c), "{:?}", c);
+            assert!(!is_whitespace(c), "{:?}", c);
+        }
+    }
+}
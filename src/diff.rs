@@ -0,0 +1,429 @@
+use super::{
+    expression::{pretty_print, Expression},
+    statement::{self, Statement},
+};
+use std::fmt;
+
+// Structural difference between two syntax trees.
+// Formatting and line numbers are ignored, only the tree shape, operators and literals matter.
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    pub path: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "at {}:", self.path)?;
+        writeln!(f, "- {}", self.left)?;
+        write!(f, "+ {}", self.right)
+    }
+}
+
+pub fn diff(left: &[Statement], right: &[Statement]) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_stmts(left, right, "statement", &mut differences);
+    differences
+}
+
+fn diff_stmts(left: &[Statement], right: &[Statement], path: &str, out: &mut Vec<Difference>) {
+    for i in 0..left.len().max(right.len()) {
+        let path = format!("{}[{}]", path, i);
+        match (left.get(i), right.get(i)) {
+            (Some(left), Some(right)) => diff_stmt(left, right, path, out),
+            (left, right) => out.push(Difference {
+                path,
+                left: left.map(print_stmt).unwrap_or_default(),
+                right: right.map(print_stmt).unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+fn print_stmt(stmt: &Statement) -> String {
+    statement::pretty_print(std::slice::from_ref(stmt))
+}
+
+fn diff_stmt(left: &Statement, right: &Statement, path: String, out: &mut Vec<Difference>) {
+    match (left, right) {
+        (Statement::Block { statements: left }, Statement::Block { statements: right }) => {
+            diff_stmts(left, right, &format!("{}.statement", path), out);
+        }
+        (
+            Statement::While {
+                condition: left_condition,
+                body: left_body,
+            },
+            Statement::While {
+                condition: right_condition,
+                body: right_body,
+            },
+        ) => {
+            diff_expr(
+                left_condition,
+                right_condition,
+                format!("{}.condition", path),
+                out,
+            );
+            diff_stmt(left_body, right_body, format!("{}.body", path), out);
+        }
+        (Statement::Class { name: left }, Statement::Class { name: right })
+            if left.lexeme == right.lexeme => {}
+        (Statement::Expression { expr: left }, Statement::Expression { expr: right })
+        | (Statement::Print { expr: left }, Statement::Print { expr: right }) => {
+            diff_expr(left, right, format!("{}.expr", path), out);
+        }
+        (
+            Statement::Var {
+                name: left_name,
+                initializer: Some(left),
+            },
+            Statement::Var {
+                name: right_name,
+                initializer: Some(right),
+            },
+        ) if left_name.lexeme == right_name.lexeme => {
+            diff_expr(left, right, format!("{}.initializer", path), out);
+        }
+        (
+            Statement::Var {
+                name: left_name,
+                initializer: None,
+            },
+            Statement::Var {
+                name: right_name,
+                initializer: None,
+            },
+        ) if left_name.lexeme == right_name.lexeme => {}
+        _ => out.push(Difference {
+            path,
+            left: print_stmt(left),
+            right: print_stmt(right),
+        }),
+    }
+}
+
+// Chains like `1 + 2 + 3` nest down the left operand, so they are walked in a loop:
+// the left ends of the chains are compared first, then the right operands from the bottom up.
+fn diff_expr(left: &Expression, right: &Expression, path: String, out: &mut Vec<Difference>) {
+    let mut chain = Vec::new();
+    let (mut left, mut right) = (left, right);
+    while let Some((left_left, left_right, right_left, right_right)) = same_operator(left, right) {
+        chain.push((left_right, right_right));
+        left = left_left;
+        right = right_left;
+    }
+    diff_node(
+        left,
+        right,
+        format!("{}{}", path, ".left".repeat(chain.len())),
+        out,
+    );
+    for (i, (left, right)) in chain.into_iter().enumerate().rev() {
+        let path = format!("{}{}.right", path, ".left".repeat(i));
+        diff_expr(left, right, path, out);
+    }
+}
+
+// The operands of two binary or logical expressions with the same operator.
+fn same_operator<'a>(
+    left: &'a Expression,
+    right: &'a Expression,
+) -> Option<(
+    &'a Expression,
+    &'a Expression,
+    &'a Expression,
+    &'a Expression,
+)> {
+    match (left, right) {
+        (
+            Expression::Binary {
+                left: left_left,
+                operator: left_operator,
+                right: left_right,
+            },
+            Expression::Binary {
+                left: right_left,
+                operator: right_operator,
+                right: right_right,
+            },
+        )
+        | (
+            Expression::Logical {
+                left: left_left,
+                operator: left_operator,
+                right: left_right,
+            },
+            Expression::Logical {
+                left: right_left,
+                operator: right_operator,
+                right: right_right,
+            },
+        ) if left_operator.t == right_operator.t => {
+            Some((left_left, left_right, right_left, right_right))
+        }
+        _ => None,
+    }
+}
+
+fn diff_node(left: &Expression, right: &Expression, path: String, out: &mut Vec<Difference>) {
+    match (left, right) {
+        (
+            Expression::Assign {
+                name: left_name,
+                value: left,
+            },
+            Expression::Assign {
+                name: right_name,
+                value: right,
+            },
+        ) if left_name.lexeme == right_name.lexeme => {
+            diff_expr(left, right, format!("{}.value", path), out);
+        }
+        (
+            Expression::Call {
+                callee: left_callee,
+                arguments: left_arguments,
+                ..
+            },
+            Expression::Call {
+                callee: right_callee,
+                arguments: right_arguments,
+                ..
+            },
+        ) if left_arguments.len() == right_arguments.len() => {
+            diff_expr(left_callee, right_callee, format!("{}.callee", path), out);
+            for (i, (left, right)) in left_arguments.iter().zip(right_arguments).enumerate() {
+                diff_expr(left, right, format!("{}.argument[{}]", path, i), out);
+            }
+        }
+        (
+            Expression::Get {
+                object: left,
+                name: left_name,
+            },
+            Expression::Get {
+                object: right,
+                name: right_name,
+            },
+        ) if left_name.lexeme == right_name.lexeme => {
+            diff_expr(left, right, format!("{}.object", path), out);
+        }
+        (
+            Expression::Set {
+                object: left_object,
+                name: left_name,
+                value: left_value,
+            },
+            Expression::Set {
+                object: right_object,
+                name: right_name,
+                value: right_value,
+            },
+        ) if left_name.lexeme == right_name.lexeme => {
+            diff_expr(left_object, right_object, format!("{}.object", path), out);
+            diff_expr(left_value, right_value, format!("{}.value", path), out);
+        }
+        (Expression::Grouping { expr: left }, Expression::Grouping { expr: right }) => {
+            diff_expr(left, right, format!("{}.group", path), out);
+        }
+        (
+            Expression::Increment {
+                operator: left_operator,
+                target: left,
+            },
+            Expression::Increment {
+                operator: right_operator,
+                target: right,
+            },
+        ) if left_operator.t == right_operator.t => {
+            diff_expr(left, right, format!("{}.target", path), out);
+        }
+        (Expression::Literal { value: left }, Expression::Literal { value: right })
+            if left == right => {}
+        (Expression::Variable { name: left }, Expression::Variable { name: right })
+            if left.lexeme == right.lexeme => {}
+        (
+            Expression::Unary {
+                operator: left_operator,
+                right: left,
+            },
+            Expression::Unary {
+                operator: right_operator,
+                right,
+            },
+        ) if left_operator.t == right_operator.t => {
+            diff_expr(left, right, format!("{}.right", path), out);
+        }
+        _ => out.push(Difference {
+            path,
+            left: pretty_print(left),
+            right: pretty_print(right),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        parser::{parse_program, Limits},
+        scanner::Scanner,
+    };
+    use super::*;
+
+    fn parse_source(source: &str) -> Vec<Statement> {
+        let tokens = Scanner::new().scan_tokens(source.to_owned()).unwrap();
+        parse_program(tokens, Limits::default()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_ignores_formatting() {
+        let left = parse_source("1 + (2 * 3);");
+        let right = parse_source("1+\n(2\n*3)\n;");
+        assert_eq!(Vec::<Difference>::new(), diff(&left, &right));
+    }
+
+    #[test]
+    fn test_diff_literal() {
+        let left = parse_source("1 + (2 * 3);");
+        let right = parse_source("1 + (2 * 4);");
+        assert_eq!(
+            vec![Difference {
+                path: "statement[0].expr.right.group.right".to_owned(),
+                left: "3".to_owned(),
+                right: "4".to_owned(),
+            }],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_chain() {
+        let left = parse_source("1 + 2 + 3 - 4;");
+        let right = parse_source("0 + 2 + 5 - 4;");
+        assert_eq!(
+            vec![
+                Difference {
+                    path: "statement[0].expr.left.left.left".to_owned(),
+                    left: "1".to_owned(),
+                    right: "0".to_owned(),
+                },
+                Difference {
+                    path: "statement[0].expr.left.right".to_owned(),
+                    left: "3".to_owned(),
+                    right: "5".to_owned(),
+                },
+            ],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_operator() {
+        let left = parse_source("-1 + 2;");
+        let right = parse_source("!1 - 2;");
+        assert_eq!(
+            vec![Difference {
+                path: "statement[0].expr".to_owned(),
+                left: "(+ (- 1) 2)".to_owned(),
+                right: "(- (! 1) 2)".to_owned(),
+            }],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_statements() {
+        let left = parse_source("print 1;");
+        let right = parse_source("1;\nprint 2;");
+        assert_eq!(
+            vec![
+                Difference {
+                    path: "statement[0]".to_owned(),
+                    left: "(print 1)".to_owned(),
+                    right: "(; 1)".to_owned(),
+                },
+                Difference {
+                    path: "statement[1]".to_owned(),
+                    left: String::new(),
+                    right: "(print 2)".to_owned(),
+                },
+            ],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_variables() {
+        let left = parse_source("var a = 1;\nvar b;\na = b;");
+        let right = parse_source("var a = 2;\nvar b;\na = a;");
+        assert_eq!(
+            vec![
+                Difference {
+                    path: "statement[0].initializer".to_owned(),
+                    left: "1".to_owned(),
+                    right: "2".to_owned(),
+                },
+                Difference {
+                    path: "statement[2].expr.value".to_owned(),
+                    left: "b".to_owned(),
+                    right: "a".to_owned(),
+                },
+            ],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_blocks() {
+        let left = parse_source("{ print 1; { print 2; } }");
+        let right = parse_source("{\n  print 1;\n  {\n    print 3;\n  }\n  print 4;\n}");
+        assert_eq!(
+            vec![
+                Difference {
+                    path: "statement[0].statement[1].statement[0].expr".to_owned(),
+                    left: "2".to_owned(),
+                    right: "3".to_owned(),
+                },
+                Difference {
+                    path: "statement[0].statement[2]".to_owned(),
+                    left: "".to_owned(),
+                    right: "(print 4)".to_owned(),
+                },
+            ],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_diff_classes() {
+        let left = parse_source("class A {}\nA().b = c(1).d;");
+        let right = parse_source("class B {}\nA().b = c(2).d;");
+        assert_eq!(
+            vec![
+                Difference {
+                    path: "statement[0]".to_owned(),
+                    left: "(class A)".to_owned(),
+                    right: "(class B)".to_owned(),
+                },
+                Difference {
+                    path: "statement[1].expr.value.object.argument[0]".to_owned(),
+                    left: "1".to_owned(),
+                    right: "2".to_owned(),
+                },
+            ],
+            diff(&left, &right)
+        );
+    }
+
+    #[test]
+    fn test_difference_display() {
+        let difference = Difference {
+            path: "root.left".to_owned(),
+            left: "1".to_owned(),
+            right: "2".to_owned(),
+        };
+        assert_eq!("at root.left:\n- 1\n+ 2", difference.to_string());
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Text encoding of a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Latin1,
+}
+
+// Decode the source file content.
+// A leading UTF-8 byte order mark is skipped.
+pub fn decode(bytes: Vec<u8>, encoding: Encoding) -> Result<String, Error> {
+    match encoding {
+        Encoding::Utf8 => {
+            let bom_len = if bytes.starts_with(UTF8_BOM) {
+                UTF8_BOM.len()
+            } else {
+                0
+            };
+            match String::from_utf8(bytes) {
+                Ok(mut text) => {
+                    text.drain(..bom_len);
+                    Ok(text)
+                }
+                Err(e) => Err(Error::InvalidUtf8 {
+                    offset: e.utf8_error().valid_up_to(),
+                }),
+            }
+        }
+        // Latin-1 bytes are the first 256 Unicode code points.
+        Encoding::Latin1 => Ok(bytes.into_iter().map(char::from).collect()),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidUtf8 { offset: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidUtf8 { offset } => write!(f, "invalid UTF-8 at byte {}", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8() {
+        let text = decode("print \"héllo\";".into(), Encoding::Utf8);
+        assert_eq!(Ok("print \"héllo\";".to_owned()), text);
+    }
+
+    #[test]
+    fn test_decode_utf8_skips_bom() {
+        let text = decode(b"\xEF\xBB\xBFprint 1;".to_vec(), Encoding::Utf8);
+        assert_eq!(Ok("print 1;".to_owned()), text);
+
+        // Only a leading mark is skipped.
+        let text = decode(b"1;\xEF\xBB\xBF".to_vec(), Encoding::Utf8);
+        assert_eq!(Ok("1;\u{feff}".to_owned()), text);
+    }
+
+    #[test]
+    fn test_decode_invalid_utf8() {
+        let text = decode(b"print \"h\xE9llo\";".to_vec(), Encoding::Utf8);
+        assert_eq!(Err(Error::InvalidUtf8 { offset: 8 }), text);
+
+        // The offset counts the byte order mark.
+        let text = decode(b"\xEF\xBB\xBF\xFF".to_vec(), Encoding::Utf8);
+        assert_eq!(Err(Error::InvalidUtf8 { offset: 3 }), text);
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        let text = decode(b"print \"h\xE9llo\";".to_vec(), Encoding::Latin1);
+        assert_eq!(Ok("print \"héllo\";".to_owned()), text);
+    }
+
+    #[test]
+    fn test_error_format() {
+        assert_eq!(
+            "invalid UTF-8 at byte 8",
+            Error::InvalidUtf8 { offset: 8 }.to_string()
+        );
+    }
+}
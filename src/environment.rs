@@ -0,0 +1,160 @@
+use super::value::Value;
+use std::collections::HashMap;
+
+// Variable bindings by name.
+// Lookups fall back to the enclosing scope, if any.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Box<Environment>>,
+}
+
+// Copy of the variables of a scope, see `Environment::snapshot`.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    values: HashMap<String, Value>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn new_with_enclosing(enclosing: Environment) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(Box::new(enclosing)),
+        }
+    }
+
+    // Drop this scope and give back the enclosing one.
+    pub fn into_enclosing(self) -> Option<Environment> {
+        self.enclosing.map(|enclosing| *enclosing)
+    }
+
+    // Define the variable, redefining it if it already exists.
+    pub fn define(&mut self, name: String, value: Value) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.get(name),
+        }
+    }
+
+    // The variables of this scope, the enclosing ones excluded.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    // Copy the variables of this scope so they can be restored later.
+    // Instances are shared with the copy: changes to their fields are not undone.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            values: self.values.clone(),
+        }
+    }
+
+    // Bring the variables of this scope back to the ones of the snapshot.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.values = snapshot.values;
+    }
+
+    // Assign to an existing variable.
+    // Return false if the variable is not defined.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => match &mut self.enclosing {
+                Some(enclosing) => enclosing.assign(name, value),
+                None => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_define_and_get() {
+        let mut env = Environment::new();
+        assert_eq!(None, env.get("foo"));
+
+        env.define("foo".to_owned(), Value::Number(1.0));
+        assert_eq!(Some(Value::Number(1.0)), env.get("foo"));
+
+        env.define("foo".to_owned(), Value::Nil);
+        assert_eq!(Some(Value::Nil), env.get("foo"));
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut env = Environment::new();
+        assert!(!env.assign("foo", Value::Nil));
+        assert_eq!(None, env.get("foo"));
+
+        env.define("foo".to_owned(), Value::Number(1.0));
+        assert!(env.assign("foo", Value::Boolean(true)));
+        assert_eq!(Some(Value::Boolean(true)), env.get("foo"));
+    }
+
+    #[test]
+    fn test_enclosing() {
+        let mut global = Environment::new();
+        global.define("foo".to_owned(), Value::Number(1.0));
+        global.define("bar".to_owned(), Value::Number(2.0));
+
+        let mut local = Environment::new_with_enclosing(global);
+        local.define("foo".to_owned(), Value::Nil);
+        assert_eq!(Some(Value::Nil), local.get("foo"));
+        assert_eq!(Some(Value::Number(2.0)), local.get("bar"));
+
+        assert!(local.assign("bar", Value::Boolean(true)));
+        assert!(local.assign("foo", Value::Boolean(false)));
+        assert!(!local.assign("baz", Value::Nil));
+
+        let global = local.into_enclosing().unwrap();
+        assert_eq!(Some(Value::Number(1.0)), global.get("foo"));
+        assert_eq!(Some(Value::Boolean(true)), global.get("bar"));
+        assert!(global.into_enclosing().is_none());
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut global = Environment::new();
+        global.define("foo".to_owned(), Value::Nil);
+        let mut local = Environment::new_with_enclosing(global);
+        local.define("bar".to_owned(), Value::Number(1.0));
+
+        let values: Vec<_> = local.iter().collect();
+        assert_eq!(vec![("bar", &Value::Number(1.0))], values);
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut env = Environment::new();
+        env.define("foo".to_owned(), Value::Number(1.0));
+        let snapshot = env.snapshot();
+
+        env.define("bar".to_owned(), Value::Nil);
+        assert!(env.assign("foo", Value::Number(2.0)));
+
+        env.restore(snapshot.clone());
+        assert_eq!(Some(Value::Number(1.0)), env.get("foo"));
+        assert_eq!(None, env.get("bar"));
+
+        // A snapshot can be restored more than once.
+        assert!(env.assign("foo", Value::Nil));
+        env.restore(snapshot);
+        assert_eq!(Some(Value::Number(1.0)), env.get("foo"));
+    }
+}
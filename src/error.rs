@@ -10,11 +10,71 @@ pub fn format_error<T: AsRef<str>>(line: usize, message: T) -> String {
     format!("[line {}] Error: {}", line, message.as_ref())
 }
 
+pub fn format_warning<T: AsRef<str>>(line: usize, message: T) -> String {
+    format!("[line {}] Warning: {}", line, message.as_ref())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    FloatEquality { token: Token },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Self::FloatEquality { token } => format_warning(
+                token.line,
+                format!(
+                    "'{}' between non-integral numbers is unreliable, compare the difference against a tolerance instead",
+                    token.t
+                ),
+            ),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum RuntimeError {
-    OperandMustBeANumber { token: Token },
-    OperandsMustBeNumbers { token: Token },
-    OperandsMustBeTwoNumbersOrTwoStrings { token: Token },
+    OperandMustBeANumber {
+        token: Token,
+    },
+    OperandsMustBeNumbers {
+        token: Token,
+    },
+    OperandsMustBeTwoNumbersOrTwoStrings {
+        token: Token,
+    },
+    OperandsMustHaveSameType {
+        token: Token,
+    },
+    UndefinedVariable {
+        token: Token,
+    },
+    NotCallable {
+        token: Token,
+    },
+    WrongArity {
+        token: Token,
+        expected: usize,
+        got: usize,
+    },
+    OnlyInstancesHaveProperties {
+        token: Token,
+    },
+    OnlyInstancesHaveFields {
+        token: Token,
+    },
+    UndefinedProperty {
+        token: Token,
+    },
+    NilPropertyAccess {
+        token: Token,
+    },
+    NilPropertyAssignment {
+        token: Token,
+    },
+    OutputFailed,
 }
 
 impl fmt::Display for RuntimeError {
@@ -29,6 +89,41 @@ impl fmt::Display for RuntimeError {
             Self::OperandsMustBeTwoNumbersOrTwoStrings { token } => {
                 format_error(token.line, "operands must be two numbers or two strings")
             }
+            Self::OperandsMustHaveSameType { token } => {
+                format_error(token.line, "operands must have the same type")
+            }
+            Self::UndefinedVariable { token } => {
+                format_error(token.line, format!("undefined variable '{}'", token.lexeme))
+            }
+            Self::NotCallable { token } => {
+                format_error(token.line, "can only call functions and classes")
+            }
+            Self::WrongArity {
+                token,
+                expected,
+                got,
+            } => format_error(
+                token.line,
+                format!("expected {} arguments but got {}", expected, got),
+            ),
+            Self::OnlyInstancesHaveProperties { token } => {
+                format_error(token.line, "only instances have properties")
+            }
+            Self::OnlyInstancesHaveFields { token } => {
+                format_error(token.line, "only instances have fields")
+            }
+            Self::UndefinedProperty { token } => {
+                format_error(token.line, format!("undefined property '{}'", token.lexeme))
+            }
+            Self::NilPropertyAccess { token } => format_error(
+                token.line,
+                format!("attempted to get property '{}' of nil", token.lexeme),
+            ),
+            Self::NilPropertyAssignment { token } => format_error(
+                token.line,
+                format!("attempted to set property '{}' of nil", token.lexeme),
+            ),
+            Self::OutputFailed => "Error: can't write the program output".to_owned(),
         };
         write!(f, "{}", msg)
     }
@@ -0,0 +1,68 @@
+// Canonical example programs, embedded so the CLI and the playground share them.
+pub struct Example {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+// Keep in sync with the examples/ directory.
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "hello-world",
+        source: include_str!("../examples/hello-world.lox"),
+    },
+    Example {
+        name: "expression",
+        source: include_str!("../examples/expression.lox"),
+    },
+    Example {
+        name: "boolean-expression",
+        source: include_str!("../examples/boolean-expression.lox"),
+    },
+    Example {
+        name: "scope",
+        source: include_str!("../examples/scope.lox"),
+    },
+    Example {
+        name: "fibonacci",
+        source: include_str!("../examples/fibonacci.lox"),
+    },
+    Example {
+        name: "classes",
+        source: include_str!("../examples/classes.lox"),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|example| example.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lox::Lox;
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_examples_run() {
+        for example in EXAMPLES {
+            let mut output = String::new();
+            let result = Lox::new().run(example.source.to_owned(), &mut output);
+            assert!(
+                result.is_ok(),
+                "example {} failed: {:?}",
+                example.name,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_examples_directory_is_embedded() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            assert!(find(name).is_some(), "example {} is not embedded", name);
+        }
+    }
+}
@@ -3,59 +3,158 @@ use std::fmt::{self, Write};
 
 #[derive(Debug)]
 pub enum Expression {
+    Assign {
+        name: Token,
+        value: Box<Expression>,
+    },
     Binary {
         left: Box<Expression>,
         operator: Token,
         right: Box<Expression>,
     },
+    Call {
+        callee: Box<Expression>,
+        paren: Token,
+        arguments: Vec<Expression>,
+    },
+    // Stands in for an expression that failed to parse, when recovering from syntax errors.
+    Error {
+        line: usize,
+    },
+    Get {
+        object: Box<Expression>,
+        name: Token,
+    },
     Grouping {
         expr: Box<Expression>,
     },
+    // `++target` or `--target`, where the target is a variable or a property.
+    Increment {
+        operator: Token,
+        target: Box<Expression>,
+    },
     Literal {
         value: TokenLiteral,
     },
+    Logical {
+        left: Box<Expression>,
+        operator: Token,
+        right: Box<Expression>,
+    },
+    Set {
+        object: Box<Expression>,
+        name: Token,
+        value: Box<Expression>,
+    },
     Unary {
         operator: Token,
         right: Box<Expression>,
     },
+    Variable {
+        name: Token,
+    },
 }
 
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Expression::Assign { name, value } => write!(f, "(= {} {})", name.lexeme, value),
             Expression::Binary {
                 left,
                 operator,
                 right,
             } => write!(f, "({} {} {})", operator.t, left, right),
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                write!(f, "(call {}", callee)?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Error { .. } => write!(f, "(error)"),
+            Expression::Get { object, name } => write!(f, "(. {} {})", object, name.lexeme),
             Expression::Grouping { expr } => write!(f, "(group {})", expr.as_ref()),
+            Expression::Increment { operator, target } => {
+                write!(f, "({} {})", operator.t, target)
+            }
             Expression::Literal { value } => write!(f, "{}", value),
+            Expression::Logical {
+                left,
+                operator,
+                right,
+            } => write!(f, "({} {} {})", operator.t, left, right),
+            Expression::Set {
+                object,
+                name,
+                value,
+            } => write!(f, "(= (. {} {}) {})", object, name.lexeme, value),
             Expression::Unary { operator, right } => write!(f, "({} {})", operator.t, right),
+            Expression::Variable { name } => write!(f, "{}", name.lexeme),
         }
     }
 }
 
 pub fn walk_expr<V: Visitor>(expr: &Expression, v: &V) -> V::Result {
     match expr {
+        Expression::Assign { name, value } => v.visit_assign(name, value),
         Expression::Binary {
             left,
             operator,
             right,
         } => v.visit_binary(left, operator, right),
+        Expression::Call {
+            callee,
+            paren,
+            arguments,
+        } => v.visit_call(callee, paren, arguments),
+        Expression::Error { line } => v.visit_error(*line),
+        Expression::Get { object, name } => v.visit_get(object, name),
         Expression::Grouping { expr } => v.visit_grouping(expr),
+        Expression::Increment { operator, target } => v.visit_increment(operator, target),
         Expression::Literal { value } => v.visit_literal(value),
+        Expression::Logical {
+            left,
+            operator,
+            right,
+        } => v.visit_logical(left, operator, right),
+        Expression::Set {
+            object,
+            name,
+            value,
+        } => v.visit_set(object, name, value),
         Expression::Unary { operator, right } => v.visit_unary(operator, right),
+        Expression::Variable { name } => v.visit_variable(name),
     }
 }
 
 pub trait Visitor {
     type Result;
 
+    fn visit_assign(&self, name: &Token, value: &Expression) -> Self::Result;
     fn visit_binary(&self, left: &Expression, operator: &Token, right: &Expression)
         -> Self::Result;
+    fn visit_call(
+        &self,
+        callee: &Expression,
+        paren: &Token,
+        arguments: &[Expression],
+    ) -> Self::Result;
+    fn visit_error(&self, line: usize) -> Self::Result;
+    fn visit_get(&self, object: &Expression, name: &Token) -> Self::Result;
     fn visit_grouping(&self, expr: &Expression) -> Self::Result;
+    fn visit_increment(&self, operator: &Token, target: &Expression) -> Self::Result;
     fn visit_literal(&self, value: &TokenLiteral) -> Self::Result;
+    fn visit_logical(
+        &self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Self::Result;
+    fn visit_set(&self, object: &Expression, name: &Token, value: &Expression) -> Self::Result;
     fn visit_unary(&self, operator: &Token, right: &Expression) -> Self::Result;
+    fn visit_variable(&self, name: &Token) -> Self::Result;
 }
 
 pub fn pretty_print(expr: &Expression) -> String {
@@ -81,6 +180,13 @@ impl AstPrinter {
 impl Visitor for AstPrinter {
     type Result = String;
 
+    fn visit_assign(&self, name: &Token, value: &Expression) -> Self::Result {
+        self.parenthesize(
+            format!("= {}", name.lexeme).as_str(),
+            vec![value].as_slice(),
+        )
+    }
+
     fn visit_binary(
         &self,
         left: &Expression,
@@ -90,17 +196,151 @@ impl Visitor for AstPrinter {
         self.parenthesize(operator.lexeme.as_str(), vec![left, right].as_slice())
     }
 
+    fn visit_call(
+        &self,
+        callee: &Expression,
+        _paren: &Token,
+        arguments: &[Expression],
+    ) -> Self::Result {
+        let exprs: Vec<&Expression> = std::iter::once(callee).chain(arguments).collect();
+        self.parenthesize("call", exprs.as_slice())
+    }
+
+    fn visit_error(&self, _line: usize) -> Self::Result {
+        "(error)".to_owned()
+    }
+
+    fn visit_get(&self, object: &Expression, name: &Token) -> Self::Result {
+        format!("(. {} {})", walk_expr(object, self), name.lexeme)
+    }
+
     fn visit_grouping(&self, expr: &Expression) -> Self::Result {
         self.parenthesize("group", vec![expr].as_slice())
     }
 
+    fn visit_increment(&self, operator: &Token, target: &Expression) -> Self::Result {
+        self.parenthesize(operator.lexeme.as_str(), vec![target].as_slice())
+    }
+
     fn visit_literal(&self, value: &TokenLiteral) -> Self::Result {
         value.to_string()
     }
 
+    fn visit_logical(
+        &self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Self::Result {
+        self.parenthesize(operator.lexeme.as_str(), vec![left, right].as_slice())
+    }
+
+    fn visit_set(&self, object: &Expression, name: &Token, value: &Expression) -> Self::Result {
+        format!(
+            "(= (. {} {}) {})",
+            walk_expr(object, self),
+            name.lexeme,
+            walk_expr(value, self)
+        )
+    }
+
     fn visit_unary(&self, operator: &Token, right: &Expression) -> Self::Result {
         self.parenthesize(operator.lexeme.as_str(), vec![right].as_slice())
     }
+
+    fn visit_variable(&self, name: &Token) -> Self::Result {
+        name.lexeme.clone()
+    }
+}
+
+pub fn rpn_print(expr: &Expression) -> String {
+    walk_expr(expr, &RpnPrinter {})
+}
+
+// Prints expressions in reverse Polish notation: operands first, then the operator.
+struct RpnPrinter;
+
+impl Visitor for RpnPrinter {
+    type Result = String;
+
+    fn visit_assign(&self, name: &Token, value: &Expression) -> Self::Result {
+        format!("{} {} =", name.lexeme, walk_expr(value, self))
+    }
+
+    fn visit_binary(
+        &self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Self::Result {
+        format!(
+            "{} {} {}",
+            walk_expr(left, self),
+            walk_expr(right, self),
+            operator.lexeme
+        )
+    }
+
+    fn visit_call(
+        &self,
+        callee: &Expression,
+        _paren: &Token,
+        arguments: &[Expression],
+    ) -> Self::Result {
+        let mut result = walk_expr(callee, self);
+        for argument in arguments {
+            result.push(' ');
+            result.push_str(&walk_expr(argument, self));
+        }
+        result.push_str(" call");
+        result
+    }
+
+    fn visit_error(&self, _line: usize) -> Self::Result {
+        "error".to_owned()
+    }
+
+    fn visit_get(&self, object: &Expression, name: &Token) -> Self::Result {
+        format!("{} {} .", walk_expr(object, self), name.lexeme)
+    }
+
+    fn visit_grouping(&self, expr: &Expression) -> Self::Result {
+        walk_expr(expr, self)
+    }
+
+    fn visit_increment(&self, operator: &Token, target: &Expression) -> Self::Result {
+        format!("{} {}", walk_expr(target, self), operator.lexeme)
+    }
+
+    fn visit_literal(&self, value: &TokenLiteral) -> Self::Result {
+        value.to_string()
+    }
+
+    fn visit_logical(
+        &self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Self::Result {
+        self.visit_binary(left, operator, right)
+    }
+
+    fn visit_set(&self, object: &Expression, name: &Token, value: &Expression) -> Self::Result {
+        format!(
+            "{} {} {} .=",
+            walk_expr(object, self),
+            name.lexeme,
+            walk_expr(value, self)
+        )
+    }
+
+    fn visit_unary(&self, operator: &Token, right: &Expression) -> Self::Result {
+        format!("{} {}", walk_expr(right, self), operator.lexeme)
+    }
+
+    fn visit_variable(&self, name: &Token) -> Self::Result {
+        name.lexeme.clone()
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +367,88 @@ mod tests {
         assert_eq!("(+ 2 4)", format!("{}", expr));
     }
 
+    fn identifier(name: &str) -> Token {
+        Token {
+            t: TokenType::Identifier,
+            lexeme: name.to_owned(),
+            literal: None,
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn test_format_assign() {
+        let expr = Expression::Assign {
+            name: identifier("foo"),
+            value: Box::new(Expression::Literal {
+                value: TokenLiteral::Number(2.0),
+            }),
+        };
+        assert_eq!("(= foo 2)", format!("{}", expr));
+        assert_eq!("(= foo 2)", pretty_print(&expr));
+        assert_eq!("foo 2 =", rpn_print(&expr));
+    }
+
+    #[test]
+    fn test_format_logical() {
+        let expr = Expression::Logical {
+            left: Box::new(Expression::Literal {
+                value: TokenLiteral::Nil,
+            }),
+            operator: Token {
+                t: TokenType::Or,
+                lexeme: "or".to_owned(),
+                literal: None,
+                line: 1,
+            },
+            right: Box::new(Expression::Literal {
+                value: TokenLiteral::Boolean(true),
+            }),
+        };
+        assert_eq!("(or nil true)", format!("{}", expr));
+        assert_eq!("(or nil true)", pretty_print(&expr));
+        assert_eq!("nil true or", rpn_print(&expr));
+    }
+
+    #[test]
+    fn test_format_properties() {
+        let name = identifier;
+        let variable = |name: &str| {
+            Box::new(Expression::Variable {
+                name: identifier(name),
+            })
+        };
+        let expr = Expression::Set {
+            object: Box::new(Expression::Call {
+                callee: variable("Foo"),
+                paren: Token {
+                    t: TokenType::RightParen,
+                    lexeme: ")".to_owned(),
+                    literal: None,
+                    line: 1,
+                },
+                arguments: vec![],
+            }),
+            name: name("bar"),
+            value: Box::new(Expression::Get {
+                object: variable("baz"),
+                name: name("qux"),
+            }),
+        };
+        assert_eq!("(= (. (call Foo) bar) (. baz qux))", format!("{}", expr));
+        assert_eq!("(= (. (call Foo) bar) (. baz qux))", pretty_print(&expr));
+        assert_eq!("Foo call bar baz qux . .=", rpn_print(&expr));
+
+        let expr = Expression::Call {
+            callee: variable("f"),
+            paren: name(")"),
+            arguments: vec![*variable("a"), *variable("b")],
+        };
+        assert_eq!("(call f a b)", format!("{}", expr));
+        assert_eq!("(call f a b)", pretty_print(&expr));
+        assert_eq!("f a b call", rpn_print(&expr));
+    }
+
     #[test]
     fn test_format_grouping() {
         let expr = Expression::Grouping {
@@ -140,9 +462,19 @@ mod tests {
     #[test]
     fn test_format_literal() {
         let expr = Expression::Literal {
-            value: TokenLiteral::Identifier("foo".to_owned()),
+            value: TokenLiteral::String("foo".to_owned()),
+        };
+        assert_eq!("\"foo\"", format!("{}", expr));
+    }
+
+    #[test]
+    fn test_format_variable() {
+        let expr = Expression::Variable {
+            name: identifier("foo"),
         };
         assert_eq!("foo", format!("{}", expr));
+        assert_eq!("foo", pretty_print(&expr));
+        assert_eq!("foo", rpn_print(&expr));
     }
 
     #[test]
@@ -218,4 +550,44 @@ mod tests {
         };
         assert_eq!("(* (- 123) (group 45.67))", pretty_print(&expr));
     }
+
+    #[test]
+    fn test_rpn_print() {
+        let expr = Expression::Binary {
+            left: Box::new(Expression::Grouping {
+                expr: Box::new(Expression::Binary {
+                    left: Box::new(Expression::Literal {
+                        value: TokenLiteral::Number(1.0),
+                    }),
+                    operator: Token {
+                        t: TokenType::Plus,
+                        lexeme: "+".to_owned(),
+                        literal: None,
+                        line: 1,
+                    },
+                    right: Box::new(Expression::Literal {
+                        value: TokenLiteral::Number(2.0),
+                    }),
+                }),
+            }),
+            operator: Token {
+                t: TokenType::Star,
+                lexeme: "*".to_owned(),
+                literal: None,
+                line: 1,
+            },
+            right: Box::new(Expression::Unary {
+                operator: Token {
+                    t: TokenType::Minus,
+                    lexeme: "-".to_owned(),
+                    literal: None,
+                    line: 1,
+                },
+                right: Box::new(Expression::Literal {
+                    value: TokenLiteral::Number(3.0),
+                }),
+            }),
+        };
+        assert_eq!("1 2 + 3 - *", rpn_print(&expr));
+    }
 }
@@ -0,0 +1,230 @@
+use super::{
+    chars,
+    lox::HighlightFormat,
+    token::{Token, TokenType},
+};
+
+// Syntactic class of a piece of source code, used to pick its color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Class {
+    Keyword,
+    Identifier,
+    String,
+    Number,
+    Operator,
+    Punctuation,
+    Comment,
+}
+
+impl Class {
+    // Name used as the CSS class in HTML output.
+    fn name(self) -> &'static str {
+        match self {
+            Class::Keyword => "keyword",
+            Class::Identifier => "identifier",
+            Class::String => "string",
+            Class::Number => "number",
+            Class::Operator => "operator",
+            Class::Punctuation => "punctuation",
+            Class::Comment => "comment",
+        }
+    }
+
+    fn ansi_color(self) -> Option<&'static str> {
+        match self {
+            Class::Keyword => Some("35"),
+            Class::String => Some("32"),
+            Class::Number => Some("36"),
+            Class::Comment => Some("90"),
+            Class::Identifier | Class::Operator | Class::Punctuation => None,
+        }
+    }
+}
+
+pub fn classify(t: TokenType) -> Class {
+    match t {
+        TokenType::LeftParen
+        | TokenType::RightParen
+        | TokenType::LeftBrace
+        | TokenType::RightBrace
+        | TokenType::Comma
+        | TokenType::Dot
+        | TokenType::Semicolon
+        | TokenType::Eof => Class::Punctuation,
+        TokenType::Minus
+        | TokenType::MinusMinus
+        | TokenType::Plus
+        | TokenType::PlusPlus
+        | TokenType::Slash
+        | TokenType::Star
+        | TokenType::Bang
+        | TokenType::BangEqual
+        | TokenType::Equal
+        | TokenType::EqualEqual
+        | TokenType::Greater
+        | TokenType::GreaterEqual
+        | TokenType::Less
+        | TokenType::LessEqual => Class::Operator,
+        TokenType::Identifier => Class::Identifier,
+        TokenType::String => Class::String,
+        TokenType::Number => Class::Number,
+        TokenType::And
+        | TokenType::Class
+        | TokenType::Else
+        | TokenType::False
+        | TokenType::Fun
+        | TokenType::For
+        | TokenType::If
+        | TokenType::Nil
+        | TokenType::Or
+        | TokenType::Print
+        | TokenType::Return
+        | TokenType::Super
+        | TokenType::This
+        | TokenType::True
+        | TokenType::Var
+        | TokenType::While => Class::Keyword,
+    }
+}
+
+// Render the source with its tokens highlighted.
+// The tokens must be the ones scanned from the source: the text between them is only
+// whitespace and comments, which are kept as is.
+pub fn highlight(source: &str, tokens: &[Token], format: HighlightFormat) -> String {
+    // The scanner normalizes line endings, so do the same to match multi-line strings.
+    let source = source.replace("\r\n", "\n").replace('\r', "\n");
+    let mut rest = source.as_str();
+    let mut out = String::new();
+
+    if format == HighlightFormat::Html {
+        out.push_str("<pre class=\"lox\"><code>");
+    }
+    for token in tokens.iter().filter(|token| token.t != TokenType::Eof) {
+        rest = write_trivia(rest, &mut out, format);
+        let (lexeme, tail) = rest.split_at(token.lexeme.len());
+        write_span(lexeme, classify(token.t), &mut out, format);
+        rest = tail;
+    }
+    write_trivia(rest, &mut out, format);
+    if format == HighlightFormat::Html {
+        out.push_str("</code></pre>\n");
+    }
+
+    out
+}
+
+// Write the whitespace and comments at the start of the source.
+// Return the source after them.
+fn write_trivia<'a>(mut rest: &'a str, out: &mut String, format: HighlightFormat) -> &'a str {
+    loop {
+        let trimmed = rest.trim_start_matches(chars::is_whitespace);
+        write_text(&rest[..rest.len() - trimmed.len()], out, format);
+        rest = trimmed;
+
+        if !rest.starts_with("//") {
+            return rest;
+        }
+        let end = rest.find('\n').unwrap_or(rest.len());
+        write_span(&rest[..end], Class::Comment, out, format);
+        rest = &rest[end..];
+    }
+}
+
+fn write_span(text: &str, class: Class, out: &mut String, format: HighlightFormat) {
+    match format {
+        HighlightFormat::Html => {
+            out.push_str(&format!("<span class=\"{}\">", class.name()));
+            write_text(text, out, format);
+            out.push_str("</span>");
+        }
+        HighlightFormat::Ansi => match class.ansi_color() {
+            Some(color) => out.push_str(&format!("\x1b[{}m{}\x1b[0m", color, text)),
+            None => out.push_str(text),
+        },
+    }
+}
+
+fn write_text(text: &str, out: &mut String, format: HighlightFormat) {
+    match format {
+        HighlightFormat::Html => {
+            for c in text.chars() {
+                match c {
+                    '&' => out.push_str("&amp;"),
+                    '<' => out.push_str("&lt;"),
+                    '>' => out.push_str("&gt;"),
+                    '"' => out.push_str("&quot;"),
+                    _ => out.push(c),
+                }
+            }
+        }
+        HighlightFormat::Ansi => out.push_str(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{keywords, scanner::Scanner, token::TOKEN_TYPES};
+    use super::*;
+
+    fn highlight_source(source: &str, format: HighlightFormat) -> String {
+        let tokens = Scanner::new().scan_tokens(source.to_owned()).unwrap();
+        highlight(source, &tokens, format)
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(Class::Keyword, classify(TokenType::While));
+        assert_eq!(Class::Keyword, classify(TokenType::Nil));
+        assert_eq!(Class::Operator, classify(TokenType::LessEqual));
+        assert_eq!(Class::Punctuation, classify(TokenType::Semicolon));
+        assert_eq!(Class::Identifier, classify(TokenType::Identifier));
+    }
+
+    #[test]
+    fn test_classify_keywords() {
+        for t in TOKEN_TYPES {
+            let keyword = keywords::lookup(&t.to_string()).is_some();
+            assert_eq!(keyword, classify(t) == Class::Keyword, "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn test_highlight_html() {
+        assert_eq!(
+            "<pre class=\"lox\"><code>\
+            <span class=\"keyword\">print</span> \
+            <span class=\"identifier\">a</span> \
+            <span class=\"operator\">&lt;</span> \
+            <span class=\"string\">&quot;&amp;&quot;</span>\
+            <span class=\"punctuation\">;</span> \
+            <span class=\"comment\">// 1 &gt; 2</span>\n\
+            </code></pre>\n",
+            highlight_source("print a < \"&\"; // 1 > 2\n", HighlightFormat::Html)
+        );
+    }
+
+    #[test]
+    fn test_highlight_ansi() {
+        assert_eq!(
+            "\x1b[35mvar\x1b[0m a = \x1b[36m1\x1b[0m;\n\x1b[90m// done\x1b[0m",
+            highlight_source("var a = 1;\n// done", HighlightFormat::Ansi)
+        );
+    }
+
+    #[test]
+    fn test_highlight_keeps_layout() {
+        let source = "{\n\t// comment\n  print \"a\nb\";\n\n}\n";
+        assert_eq!(
+            "{\n\t// comment\n  print \"a\nb\";\n\n}\n",
+            highlight_source(source, HighlightFormat::Ansi)
+                .replace("\x1b[0m", "")
+                .replace("\x1b[35m", "")
+                .replace("\x1b[32m", "")
+                .replace("\x1b[90m", "")
+        );
+        assert_eq!(
+            "\x1b[32m\"a\nb\"\x1b[0m\n",
+            highlight_source("\"a\r\nb\"\r\n", HighlightFormat::Ansi)
+        );
+    }
+}
@@ -1,13 +1,128 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    fmt,
+    ops::Deref,
+    rc::Rc,
+};
+
 use super::{
-    error::RuntimeError,
-    expression::{walk_expr, Expression, Visitor},
+    environment::{Environment, Snapshot},
+    error::{RuntimeError, Warning},
+    expression::{self, walk_expr, Expression},
+    statement::{self, walk_stmt, Statement},
     token::{Literal as TokenLiteral, Token, TokenType},
-    value::Value,
+    value::{Class, Instance, Value},
 };
 
-pub struct Interpreter {}
+pub struct Interpreter {
+    strict_equality: bool,
+    warn_float_equality: bool,
+    warnings: RefCell<Vec<Warning>>,
+    environment: RefCell<Environment>,
+    // Number of nested environments, the global one included.
+    depth: Cell<usize>,
+    statistics: Cell<Statistics>,
+}
+
+// Counters about the execution, for the run summary.
+// They count nodes of the syntax tree after desugaring, so a `for` loop
+// adds the blocks it is rewritten into.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Statistics {
+    pub statements: usize,
+    pub peak_depth: usize,
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "statements executed: {} (syntax tree nodes, after desugaring)",
+            self.statements
+        )?;
+        write!(f, "peak environment depth: {}", self.peak_depth)
+    }
+}
+
+// The interpreter while it runs a program: printed values are written
+// to the output as soon as they are printed.
+struct Execution<'a> {
+    interpreter: &'a Interpreter,
+    output: RefCell<&'a mut dyn fmt::Write>,
+    // Operators of the program already warned about, by address in the syntax tree.
+    warned: RefCell<HashSet<*const Token>>,
+}
+
+impl Deref for Execution<'_> {
+    type Target = Interpreter;
+
+    fn deref(&self) -> &Interpreter {
+        self.interpreter
+    }
+}
+
+impl statement::Visitor for Execution<'_> {
+    type Result = std::result::Result<(), RuntimeError>;
+
+    fn visit_expression_stmt(&self, expr: &Expression) -> Self::Result {
+        self.evaluate(expr)?;
+        Ok(())
+    }
+
+    fn visit_block_stmt(&self, statements: &[Statement]) -> Self::Result {
+        let enclosing = self.environment.take();
+        self.environment
+            .replace(Environment::new_with_enclosing(enclosing));
+        self.depth.set(self.depth.get() + 1);
+        let mut statistics = self.statistics.get();
+        statistics.peak_depth = statistics.peak_depth.max(self.depth.get());
+        self.statistics.set(statistics);
+
+        let result = self.interpret(statements);
+
+        self.depth.set(self.depth.get() - 1);
+        let enclosing = self.environment.take().into_enclosing().unwrap();
+        self.environment.replace(enclosing);
+        result
+    }
+
+    fn visit_class_stmt(&self, name: &Token) -> Self::Result {
+        let class = Class {
+            name: name.lexeme.clone(),
+        };
+        self.environment
+            .borrow_mut()
+            .define(name.lexeme.clone(), Value::Class(Rc::new(class)));
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, expr: &Expression) -> Self::Result {
+        let value = self.evaluate(expr)?;
+        // The reader of the output may have gone away, as when piped into `head`.
+        writeln!(self.output.borrow_mut(), "{}", value).map_err(|_| RuntimeError::OutputFailed)
+    }
+
+    fn visit_while_stmt(&self, condition: &Expression, body: &Statement) -> Self::Result {
+        while is_truthy(&self.evaluate(condition)?) {
+            self.execute(body)?;
+        }
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, name: &Token, initializer: Option<&Expression>) -> Self::Result {
+        let value = match initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Value::Nil,
+        };
+        self.environment
+            .borrow_mut()
+            .define(name.lexeme.clone(), value);
+        Ok(())
+    }
+}
 
-impl Visitor for Interpreter {
+impl expression::Visitor for Execution<'_> {
     type Result = Result;
 
     fn visit_literal(&self, value: &TokenLiteral) -> Result {
@@ -16,14 +131,153 @@ impl Visitor for Interpreter {
             TokenLiteral::Boolean(b) => Ok(Value::Boolean(*b)),
             TokenLiteral::Number(num) => Ok(Value::Number(*num)),
             TokenLiteral::String(s) => Ok(Value::String(s.clone())),
-            TokenLiteral::Identifier(_s) => todo!(),
         }
     }
 
+    fn visit_variable(&self, name: &Token) -> Result {
+        self.environment
+            .borrow()
+            .get(&name.lexeme)
+            .ok_or_else(|| RuntimeError::UndefinedVariable {
+                token: name.clone(),
+            })
+    }
+
+    fn visit_assign(&self, name: &Token, value: &Expression) -> Result {
+        let value = self.evaluate(value)?;
+        if self
+            .environment
+            .borrow_mut()
+            .assign(&name.lexeme, value.clone())
+        {
+            Ok(value)
+        } else {
+            Err(RuntimeError::UndefinedVariable {
+                token: name.clone(),
+            })
+        }
+    }
+
+    fn visit_call(&self, callee: &Expression, paren: &Token, arguments: &[Expression]) -> Result {
+        let callee = self.evaluate(callee)?;
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        match callee {
+            // Classes have no initializer yet, so they take no arguments.
+            Value::Class(class) => {
+                if !arguments.is_empty() {
+                    return Err(RuntimeError::WrongArity {
+                        token: paren.clone(),
+                        expected: 0,
+                        got: arguments.len(),
+                    });
+                }
+                let instance = Instance::new(class);
+                Ok(Value::Instance(Rc::new(RefCell::new(instance))))
+            }
+            _ => Err(RuntimeError::NotCallable {
+                token: paren.clone(),
+            }),
+        }
+    }
+
+    fn visit_error(&self, _line: usize) -> Result {
+        unreachable!("only trees parsed without errors are interpreted")
+    }
+
+    fn visit_get(&self, object: &Expression, name: &Token) -> Result {
+        get_property(&self.evaluate(object)?, name)
+    }
+
+    fn visit_set(&self, object: &Expression, name: &Token, value: &Expression) -> Result {
+        let instance = match self.evaluate(object)? {
+            Value::Instance(instance) => instance,
+            Value::Nil => {
+                return Err(RuntimeError::NilPropertyAssignment {
+                    token: name.clone(),
+                })
+            }
+            _ => {
+                return Err(RuntimeError::OnlyInstancesHaveFields {
+                    token: name.clone(),
+                })
+            }
+        };
+        let value = self.evaluate(value)?;
+        instance
+            .borrow_mut()
+            .set(name.lexeme.clone(), value.clone());
+        Ok(value)
+    }
+
     fn visit_grouping(&self, expr: &Expression) -> Result {
         self.evaluate(expr)
     }
 
+    // The object of a property is evaluated once, for both reading and writing the field.
+    fn visit_increment(&self, operator: &Token, target: &Expression) -> Result {
+        let step = |value: Value| {
+            check_number_operand(&value, operator)?;
+            let delta = match operator.t {
+                TokenType::PlusPlus => 1.0,
+                _ => -1.0,
+            };
+            Ok(Value::Number(value.unwrap_number() + delta))
+        };
+        match target {
+            Expression::Variable { name } => {
+                let value = step(self.visit_variable(name)?)?;
+                self.environment
+                    .borrow_mut()
+                    .assign(&name.lexeme, value.clone());
+                Ok(value)
+            }
+            Expression::Get { object, name } => {
+                let object = self.evaluate(object)?;
+                let value = step(get_property(&object, name)?)?;
+                if let Value::Instance(instance) = object {
+                    instance
+                        .borrow_mut()
+                        .set(name.lexeme.clone(), value.clone());
+                }
+                Ok(value)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // Short-circuit: the right operand is only evaluated when the left one doesn't decide.
+    // Chains like `a or b or c` nest down the left operand and are evaluated in a loop.
+    fn visit_logical(&self, left: &Expression, operator: &Token, right: &Expression) -> Result {
+        let mut chain = vec![(operator, right)];
+        let mut left = left;
+        while let Expression::Logical {
+            left: inner,
+            operator,
+            right,
+        } = left
+        {
+            chain.push((operator, right));
+            left = inner;
+        }
+
+        let mut value = self.evaluate(left)?;
+        for (operator, right) in chain.into_iter().rev() {
+            let decided = match operator.t {
+                TokenType::Or => is_truthy(&value),
+                TokenType::And => !is_truthy(&value),
+                _ => unreachable!(),
+            };
+            if !decided {
+                value = self.evaluate(right)?;
+            }
+        }
+        Ok(value)
+    }
+
     fn visit_unary(&self, operator: &Token, right: &Expression) -> Result {
         let right = self.evaluate(right)?;
 
@@ -37,10 +291,144 @@ impl Visitor for Interpreter {
         }
     }
 
+    // Chains like `1 + 2 + 3` nest down the left operand and are evaluated in a loop,
+    // so their length isn't limited by the stack.
     fn visit_binary(&self, left: &Expression, operator: &Token, right: &Expression) -> Result {
-        let left = self.evaluate(left)?;
-        let right = self.evaluate(right)?;
+        let mut chain = vec![(operator, right)];
+        let mut left = left;
+        while let Expression::Binary {
+            left: inner,
+            operator,
+            right,
+        } = left
+        {
+            chain.push((operator, right));
+            left = inner;
+        }
+
+        let mut value = self.evaluate(left)?;
+        for (operator, right) in chain.into_iter().rev() {
+            let right = self.evaluate(right)?;
+            value = self.binary(value, operator, right)?;
+        }
+        Ok(value)
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            strict_equality: false,
+            warn_float_equality: false,
+            warnings: RefCell::new(Vec::new()),
+            environment: RefCell::new(Environment::new()),
+            depth: Cell::new(1),
+            statistics: Cell::new(Statistics {
+                statements: 0,
+                peak_depth: 1,
+            }),
+        }
+    }
+
+    // In strict equality mode comparing values of different types is a runtime error
+    // instead of being false.
+    pub fn strict_equality(mut self, enabled: bool) -> Self {
+        self.strict_equality = enabled;
+        self
+    }
+
+    // Warn when `==` or `!=` compares two non-integral numbers.
+    pub fn warn_float_equality(mut self, enabled: bool) -> Self {
+        self.warn_float_equality = enabled;
+        self
+    }
+
+    // Take the warnings collected since the last call.
+    pub fn take_warnings(&self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        self.statistics.get()
+    }
+
+    // The global variables, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let environment = self.environment.borrow();
+        let mut globals: Vec<_> = environment
+            .iter()
+            .map(|(name, value)| (name.to_owned(), value.clone()))
+            .collect();
+        globals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        globals
+    }
+
+    pub fn snapshot_globals(&self) -> Snapshot {
+        self.environment.borrow().snapshot()
+    }
+
+    pub fn restore_globals(&self, snapshot: Snapshot) {
+        self.environment.borrow_mut().restore(snapshot);
+    }
+
+    // Run the statements writing printed values to the output as they are printed.
+    pub fn interpret(
+        &self,
+        statements: &[Statement],
+        output: &mut dyn fmt::Write,
+    ) -> std::result::Result<(), RuntimeError> {
+        self.execution(output).interpret(statements)
+    }
 
+    // Expressions print nothing, so there is no output.
+    pub fn evaluate(&self, expr: &Expression) -> Result {
+        self.execution(&mut String::new()).evaluate(expr)
+    }
+
+    fn execution<'a>(&'a self, output: &'a mut dyn fmt::Write) -> Execution<'a> {
+        Execution {
+            interpreter: self,
+            output: RefCell::new(output),
+            warned: RefCell::new(HashSet::new()),
+        }
+    }
+
+    fn check_comparable_operands(
+        &self,
+        left: &Value,
+        right: &Value,
+        operator: &Token,
+    ) -> std::result::Result<(), RuntimeError> {
+        if !self.strict_equality || std::mem::discriminant(left) == std::mem::discriminant(right) {
+            Ok(())
+        } else {
+            Err(RuntimeError::OperandsMustHaveSameType {
+                token: operator.clone(),
+            })
+        }
+    }
+}
+
+impl Execution<'_> {
+    fn interpret(&self, statements: &[Statement]) -> std::result::Result<(), RuntimeError> {
+        for stmt in statements {
+            self.execute(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self, expr: &Expression) -> Result {
+        walk_expr(expr, self)
+    }
+
+    fn execute(&self, stmt: &Statement) -> std::result::Result<(), RuntimeError> {
+        let mut statistics = self.statistics.get();
+        statistics.statements += 1;
+        self.statistics.set(statistics);
+        walk_stmt(stmt, self)
+    }
+
+    fn binary(&self, left: Value, operator: &Token, right: Value) -> Result {
         match operator.t {
             TokenType::Plus => {
                 if left.is_number() && right.is_number() {
@@ -87,24 +475,35 @@ impl Visitor for Interpreter {
                     left.unwrap_number() <= right.unwrap_number(),
                 ))
             }
-            TokenType::EqualEqual => Ok(Value::Boolean(is_equal(&left, &right))),
-            TokenType::BangEqual => Ok(Value::Boolean(!is_equal(&left, &right))),
+            TokenType::EqualEqual => {
+                self.check_comparable_operands(&left, &right, operator)?;
+                self.check_float_equality(&left, &right, operator);
+                Ok(Value::Boolean(is_equal(&left, &right)))
+            }
+            TokenType::BangEqual => {
+                self.check_comparable_operands(&left, &right, operator)?;
+                self.check_float_equality(&left, &right, operator);
+                Ok(Value::Boolean(!is_equal(&left, &right)))
+            }
             _ => unreachable!(),
         }
     }
-}
-
-impl Interpreter {
-    pub fn new() -> Self {
-        Self {}
-    }
 
-    pub fn interpret(&self, expr: &Expression) -> Result {
-        self.evaluate(expr)
-    }
-
-    fn evaluate(&self, expr: &Expression) -> Result {
-        walk_expr(expr, self)
+    // Each operator is warned about once, even when it runs in a loop.
+    fn check_float_equality(&self, left: &Value, right: &Value, operator: &Token) {
+        if !self.warn_float_equality {
+            return;
+        }
+        if let (Value::Number(left), Value::Number(right)) = (left, right) {
+            if left.fract() != 0.0
+                && right.fract() != 0.0
+                && self.warned.borrow_mut().insert(operator as *const Token)
+            {
+                self.warnings.borrow_mut().push(Warning::FloatEquality {
+                    token: operator.clone(),
+                });
+            }
+        }
     }
 }
 
@@ -125,6 +524,27 @@ fn is_equal(left: &Value, right: &Value) -> bool {
         Value::Boolean(b) => right.is_boolean() && *b == right.unwrap_boolean(),
         Value::Number(num) => right.is_number() && *num == right.unwrap_number(),
         Value::String(s) => right.is_string() && s == right.unwrap_string(),
+        Value::Class(_) | Value::Instance(_) => left == right,
+    }
+}
+
+fn get_property(object: &Value, name: &Token) -> Result {
+    match object {
+        Value::Instance(instance) => {
+            instance
+                .borrow()
+                .get(&name.lexeme)
+                .ok_or_else(|| RuntimeError::UndefinedProperty {
+                    token: name.clone(),
+                })
+        }
+        // Reading a property of an unset variable or field is the most common mistake.
+        Value::Nil => Err(RuntimeError::NilPropertyAccess {
+            token: name.clone(),
+        }),
+        _ => Err(RuntimeError::OnlyInstancesHaveProperties {
+            token: name.clone(),
+        }),
     }
 }
 
@@ -161,7 +581,7 @@ mod tests {
 
     fn interpret(expr: &Expression) -> Result {
         let interpreter = Interpreter::new();
-        interpreter.interpret(expr)
+        interpreter.evaluate(expr)
     }
 
     #[test]
@@ -576,4 +996,294 @@ mod tests {
             assert_eq!(Ok(Value::Boolean(!true_result)), interpret(&expr));
         }
     }
+
+    #[test]
+    fn interpret_strict_equality() {
+        let interpreter = Interpreter::new().strict_equality(true);
+        for t in [TokenType::EqualEqual, TokenType::BangEqual] {
+            let operator = Token {
+                t,
+                line: 1,
+                lexeme: String::new(),
+                literal: None,
+            };
+            let expr = Expression::Binary {
+                left: Box::new(Expression::Literal {
+                    value: TokenLiteral::Number(2.0),
+                }),
+                operator: operator.clone(),
+                right: Box::new(Expression::Literal {
+                    value: TokenLiteral::String("2".to_owned()),
+                }),
+            };
+            assert_eq!(
+                Err(RuntimeError::OperandsMustHaveSameType { token: operator }),
+                interpreter.evaluate(&expr)
+            );
+        }
+
+        let expr = Expression::Binary {
+            left: Box::new(Expression::Literal {
+                value: TokenLiteral::Number(2.0),
+            }),
+            operator: Token {
+                t: TokenType::EqualEqual,
+                line: 1,
+                lexeme: String::new(),
+                literal: None,
+            },
+            right: Box::new(Expression::Literal {
+                value: TokenLiteral::Number(2.0),
+            }),
+        };
+        assert_eq!(Ok(Value::Boolean(true)), interpreter.evaluate(&expr));
+    }
+
+    #[test]
+    fn interpret_float_equality_warning() {
+        let data = vec![(0.1, 0.3, true), (0.5, 2.0, false), (1.0, 2.0, false)];
+
+        for (left, right, warns) in data {
+            let interpreter = Interpreter::new().warn_float_equality(true);
+            let operator = Token {
+                t: TokenType::EqualEqual,
+                line: 1,
+                lexeme: "==".to_owned(),
+                literal: None,
+            };
+            let expr = Expression::Binary {
+                left: Box::new(Expression::Literal {
+                    value: TokenLiteral::Number(left),
+                }),
+                operator: operator.clone(),
+                right: Box::new(Expression::Literal {
+                    value: TokenLiteral::Number(right),
+                }),
+            };
+            assert!(interpreter.evaluate(&expr).is_ok());
+            let expected = if warns {
+                vec![Warning::FloatEquality { token: operator }]
+            } else {
+                vec![]
+            };
+            assert_eq!(expected, interpreter.take_warnings());
+            assert_eq!(Vec::<Warning>::new(), interpreter.take_warnings());
+        }
+    }
+
+    #[test]
+    fn interpret_print_statement() {
+        let interpreter = Interpreter::new();
+        let statements = vec![
+            Statement::Print {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Number(1.0),
+                },
+            },
+            Statement::Expression {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Number(2.0),
+                },
+            },
+            Statement::Print {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Boolean(true),
+                },
+            },
+        ];
+
+        let mut output = String::new();
+        assert_eq!(Ok(()), interpreter.interpret(&statements, &mut output));
+        assert_eq!("1\ntrue\n", output);
+    }
+
+    #[test]
+    fn interpret_stops_at_runtime_error() {
+        let interpreter = Interpreter::new();
+        let operator = Token {
+            t: TokenType::Minus,
+            line: 1,
+            lexeme: "-".to_owned(),
+            literal: None,
+        };
+        let statements = vec![
+            Statement::Print {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Number(1.0),
+                },
+            },
+            Statement::Print {
+                expr: Expression::Unary {
+                    operator: operator.clone(),
+                    right: Box::new(Expression::Literal {
+                        value: TokenLiteral::Nil,
+                    }),
+                },
+            },
+            Statement::Print {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Number(3.0),
+                },
+            },
+        ];
+
+        let mut output = String::new();
+        assert_eq!(
+            Err(RuntimeError::OperandMustBeANumber { token: operator }),
+            interpreter.interpret(&statements, &mut output)
+        );
+        assert_eq!("1\n", output);
+    }
+
+    #[test]
+    fn interpret_variables() {
+        let interpreter = Interpreter::new();
+        let name = Token {
+            t: TokenType::Identifier,
+            line: 1,
+            lexeme: "foo".to_owned(),
+            literal: None,
+        };
+        let variable = || Expression::Variable { name: name.clone() };
+        let statements = vec![
+            Statement::Var {
+                name: name.clone(),
+                initializer: None,
+            },
+            Statement::Print { expr: variable() },
+            Statement::Var {
+                name: name.clone(),
+                initializer: Some(Expression::Literal {
+                    value: TokenLiteral::Number(1.0),
+                }),
+            },
+            Statement::Print { expr: variable() },
+            Statement::Print {
+                expr: Expression::Assign {
+                    name: name.clone(),
+                    value: Box::new(Expression::Literal {
+                        value: TokenLiteral::Number(2.0),
+                    }),
+                },
+            },
+            Statement::Print { expr: variable() },
+        ];
+
+        let mut output = String::new();
+        assert_eq!(Ok(()), interpreter.interpret(&statements, &mut output));
+        assert_eq!("nil\n1\n2\n2\n", output);
+    }
+
+    #[test]
+    fn interpret_undefined_variable() {
+        let name = Token {
+            t: TokenType::Identifier,
+            line: 2,
+            lexeme: "foo".to_owned(),
+            literal: None,
+        };
+        let expr = Expression::Variable { name: name.clone() };
+        assert_eq!(
+            Err(RuntimeError::UndefinedVariable {
+                token: name.clone()
+            }),
+            interpret(&expr)
+        );
+
+        let expr = Expression::Assign {
+            name: name.clone(),
+            value: Box::new(Expression::Literal {
+                value: TokenLiteral::Nil,
+            }),
+        };
+        assert_eq!(
+            Err(RuntimeError::UndefinedVariable { token: name }),
+            interpret(&expr)
+        );
+    }
+
+    #[test]
+    fn interpret_block_scope() {
+        let interpreter = Interpreter::new();
+        let var = |name: &str, value: f64| Statement::Var {
+            name: Token {
+                t: TokenType::Identifier,
+                line: 1,
+                lexeme: name.to_owned(),
+                literal: None,
+            },
+            initializer: Some(Expression::Literal {
+                value: TokenLiteral::Number(value),
+            }),
+        };
+        let print = |name: &str| Statement::Print {
+            expr: Expression::Variable {
+                name: Token {
+                    t: TokenType::Identifier,
+                    line: 1,
+                    lexeme: name.to_owned(),
+                    literal: None,
+                },
+            },
+        };
+        let statements = vec![
+            var("a", 1.0),
+            Statement::Block {
+                statements: vec![var("a", 2.0), var("b", 3.0), print("a"), print("b")],
+            },
+            print("a"),
+        ];
+
+        let mut output = String::new();
+        assert_eq!(Ok(()), interpreter.interpret(&statements, &mut output));
+        assert_eq!("2\n3\n1\n", output);
+
+        // the scope is popped even when the block fails
+        let statements = vec![Statement::Block {
+            statements: vec![var("c", 1.0), print("d")],
+        }];
+        let mut output = String::new();
+        assert!(interpreter.interpret(&statements, &mut output).is_err());
+        assert_eq!("", output);
+        assert!(interpreter.interpret(&[print("c")], &mut output).is_err());
+        assert_eq!(Ok(()), interpreter.interpret(&[print("a")], &mut output));
+    }
+
+    #[test]
+    fn interpret_statistics() {
+        let interpreter = Interpreter::new();
+        let print = || Statement::Print {
+            expr: Expression::Literal {
+                value: TokenLiteral::Nil,
+            },
+        };
+        let statements = vec![
+            print(),
+            Statement::Block {
+                statements: vec![
+                    print(),
+                    Statement::Block {
+                        statements: vec![print()],
+                    },
+                ],
+            },
+            Statement::Block { statements: vec![] },
+        ];
+
+        assert_eq!(
+            Ok(()),
+            interpreter.interpret(&statements, &mut String::new())
+        );
+        assert_eq!(
+            Statistics {
+                statements: 6,
+                peak_depth: 3,
+            },
+            interpreter.statistics()
+        );
+        assert_eq!(
+            "statements executed: 6 (syntax tree nodes, after desugaring)\npeak environment depth: 3",
+            interpreter.statistics().to_string()
+        );
+    }
 }
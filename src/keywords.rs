@@ -0,0 +1,91 @@
+use super::token::TokenType;
+
+// Token type of the reserved word, or None if the word is an identifier.
+// Adding a keyword takes a new arm here and a token type.
+pub fn lookup(word: &str) -> Option<TokenType> {
+    let t = match word {
+        "and" => TokenType::And,
+        "class" => TokenType::Class,
+        "else" => TokenType::Else,
+        "false" => TokenType::False,
+        "for" => TokenType::For,
+        "fun" => TokenType::Fun,
+        "if" => TokenType::If,
+        "nil" => TokenType::Nil,
+        "or" => TokenType::Or,
+        "print" => TokenType::Print,
+        "return" => TokenType::Return,
+        "super" => TokenType::Super,
+        "this" => TokenType::This,
+        "true" => TokenType::True,
+        "var" => TokenType::Var,
+        "while" => TokenType::While,
+        _ => return None,
+    };
+    Some(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::token::TOKEN_TYPES;
+    use super::*;
+
+    #[test]
+    fn test_lookup_every_keyword() {
+        for t in TOKEN_TYPES {
+            // Keyword token types display as their word.
+            let word = t.to_string();
+            // No wildcard: a new token type does not compile until it is sorted in here.
+            match t {
+                TokenType::And
+                | TokenType::Class
+                | TokenType::Else
+                | TokenType::False
+                | TokenType::Fun
+                | TokenType::For
+                | TokenType::If
+                | TokenType::Nil
+                | TokenType::Or
+                | TokenType::Print
+                | TokenType::Return
+                | TokenType::Super
+                | TokenType::This
+                | TokenType::True
+                | TokenType::Var
+                | TokenType::While => assert_eq!(Some(t), lookup(&word)),
+                TokenType::LeftParen
+                | TokenType::RightParen
+                | TokenType::LeftBrace
+                | TokenType::RightBrace
+                | TokenType::Comma
+                | TokenType::Dot
+                | TokenType::Minus
+                | TokenType::Plus
+                | TokenType::Semicolon
+                | TokenType::Slash
+                | TokenType::Star
+                | TokenType::MinusMinus
+                | TokenType::PlusPlus
+                | TokenType::Bang
+                | TokenType::BangEqual
+                | TokenType::Equal
+                | TokenType::EqualEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+                | TokenType::Identifier
+                | TokenType::String
+                | TokenType::Number
+                | TokenType::Eof => assert_eq!(None, lookup(&word)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_identifiers() {
+        for word in ["", "foo", "And", "classes", "whil", "_if", "nil2", "eof"] {
+            assert_eq!(None, lookup(word), "{:?}", word);
+        }
+    }
+}
@@ -2,40 +2,171 @@ use std::{
     fmt, fs,
     io::{self, Write},
     process,
+    time::{Duration, Instant},
 };
 use wasm_bindgen::prelude::*;
 
+mod chars;
+mod diff;
+mod encoding;
+mod environment;
 mod error;
+mod examples;
 mod expression;
+mod highlight;
 mod interpreter;
+mod keywords;
 mod lox;
 mod parser;
 mod scanner;
+mod statement;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 mod token;
 mod value;
 
-pub fn run_file(file: String) {
-    let text = fs::read_to_string(file).expect("file read failed");
-    let err = run_print_stdout(text);
+pub use diff::Difference;
+pub use encoding::Encoding;
+pub use environment::Snapshot;
+pub use error::{RuntimeError, Warning};
+pub use expression::Expression;
+pub use interpreter::Statistics;
+pub use lox::{AstFormat, Error, HighlightFormat, Lox, LoxBuilder};
+// The parser is public for tooling that works on the syntax tree without running it.
+pub use parser::{parse_program, parse_tokens, Error as ParseError, Input, Limits, Parser};
+pub use statement::Statement;
+pub use token::{Literal, Token, TokenType};
+pub use value::Value;
+
+// Exit codes follow the BSD sysexits convention.
+pub const EXIT_DIFFERENT: i32 = 1;
+pub const EXIT_USAGE: i32 = 64;
+pub const EXIT_DATA_ERROR: i32 = 65;
+pub const EXIT_NO_INPUT: i32 = 66;
+pub const EXIT_SOFTWARE: i32 = 70;
+pub const EXIT_IO_ERROR: i32 = 74;
+
+// With `summary` the execution counters are printed to stderr once the script is done.
+pub fn run_file(file: String, builder: LoxBuilder, encoding: Encoding, summary: bool) {
+    let text = read_file_or_exit(&file, encoding);
+    let mut lox = builder.build();
+    let err = run_print_stdout(&mut lox, text);
+    if summary {
+        eprintln!("{}", lox.statistics());
+    }
     if let Some(err) = err {
-        match err {
-            ExecErrorType::RuntimeError => process::exit(70),
-            _ => process::exit(65),
+        process::exit(err.exit_code());
+    }
+}
+
+pub fn dump_file_ast(file: String, format: AstFormat, encoding: Encoding) {
+    let text = read_file_or_exit(&file, encoding);
+    let lox = lox::Lox::new();
+    match lox.dump_ast(text, format) {
+        Ok((dump, errors)) => {
+            println!("{}", dump);
+            for e in &errors {
+                eprintln!("{}", e);
+            }
+            if !errors.is_empty() {
+                process::exit(EXIT_DATA_ERROR);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(EXIT_DATA_ERROR);
+        }
+    }
+}
+
+pub fn tokenize_file(file: String, encoding: Encoding) {
+    let text = read_file_or_exit(&file, encoding);
+    let lox = lox::Lox::new();
+    match lox.tokenize(text) {
+        Ok(dump) => println!("{}", dump),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(EXIT_DATA_ERROR);
         }
     }
 }
 
-pub fn dump_file_ast(file: String) {
-    let text = fs::read_to_string(file).expect("file read failed");
+pub fn highlight_file(file: String, format: HighlightFormat, encoding: Encoding) {
+    let text = read_file_or_exit(&file, encoding);
     let lox = lox::Lox::new();
-    match lox.dump_ast(text) {
-        Ok(value) => println!("{}", value),
-        Err(e) => eprintln!("{}", e),
+    match lox.highlight(text, format) {
+        Ok(value) => print!("{}", value),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(EXIT_DATA_ERROR);
+        }
+    }
+}
+
+// Print the structural differences between the syntax trees of two files.
+// Exit with 1 if there are any, like diff does.
+pub fn diff_file_ast(left: String, right: String, encoding: Encoding) {
+    let left = read_file_or_exit(&left, encoding);
+    let right = read_file_or_exit(&right, encoding);
+    let lox = lox::Lox::new();
+    match lox.diff_ast(left, right) {
+        Ok(differences) if differences.is_empty() => {}
+        Ok(differences) => {
+            for difference in differences {
+                println!("{}", difference);
+            }
+            process::exit(EXIT_DIFFERENT);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(EXIT_DATA_ERROR);
+        }
     }
 }
 
-pub fn run_prompt() {
+pub fn print_grammar() {
+    print!("{}", parser::grammar_ebnf());
+}
+
+// List the example programs, or run the one with the given name.
+pub fn run_example(name: Option<String>) {
+    let Some(name) = name else {
+        for example in examples::EXAMPLES {
+            println!("{}", example.name);
+        }
+        return;
+    };
+    let Some(example) = examples::find(&name) else {
+        eprintln!("unknown example {}", name);
+        process::exit(EXIT_NO_INPUT);
+    };
+    let mut lox = lox::Lox::new();
+    if let Some(err) = run_print_stdout(&mut lox, example.source.to_owned()) {
+        process::exit(err.exit_code());
+    }
+}
+
+fn read_file_or_exit(file: &str, encoding: Encoding) -> String {
+    let bytes = match fs::read(file) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("can't read {}: {}", file, e);
+            process::exit(EXIT_NO_INPUT);
+        }
+    };
+    match encoding::decode(bytes, encoding) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("can't read {}: {}", file, e);
+            process::exit(EXIT_DATA_ERROR);
+        }
+    }
+}
+
+pub fn run_prompt(builder: LoxBuilder) {
     let stdin = io::stdin();
+    let mut lox = builder.build();
+    let mut last_elapsed = None;
     loop {
         print!("> ");
         io::stdout().flush().unwrap();
@@ -47,50 +178,119 @@ pub fn run_prompt() {
             break;
         }
 
-        run_print_stdout(input);
+        if let Some(command) = input.trim().strip_prefix(':') {
+            run_meta_command(command, &lox, last_elapsed);
+            continue;
+        }
+
+        let start = Instant::now();
+        run_print_stdout(&mut lox, input);
+        last_elapsed = Some(start.elapsed());
+    }
+}
+
+// Run a REPL command such as `:time`.
+// The elapsed time is the one of the last evaluated input.
+fn run_meta_command(command: &str, lox: &Lox, last_elapsed: Option<Duration>) {
+    match command {
+        "env" => {
+            for (name, value) in lox.globals() {
+                println!("{} = {}", name, value.repr());
+            }
+        }
+        "time" => match last_elapsed {
+            Some(elapsed) => println!("last evaluation took {:?}", elapsed),
+            None => println!("nothing evaluated yet"),
+        },
+        _ => eprintln!("unknown command :{}", command),
     }
 }
 
 #[wasm_bindgen]
 pub fn run_wasm(source: String) -> String {
-    let result = run_with_result(source);
-    result.output
+    let mut lox = lox::Lox::new();
+    let mut output = String::new();
+    let mut echo = String::new();
+    let mut diagnostics = String::new();
+    run_with_output(&mut lox, source, &mut output, &mut echo, &mut diagnostics);
+    // The playground has a single output area: diagnostics go after the program output.
+    output + &echo + &diagnostics
 }
 
-fn run_print_stdout(source: String) -> Option<ExecErrorType> {
-    let result = run_with_result(source);
-    println!("{}", result.output);
-    result.err
+#[wasm_bindgen]
+pub fn example_names() -> Vec<String> {
+    examples::EXAMPLES
+        .iter()
+        .map(|example| example.name.to_owned())
+        .collect()
 }
 
-fn run_with_result(source: String) -> ExecutionResult {
-    let mut output = String::new();
-    let err = run_with_output(source, &mut output);
-    ExecutionResult { output, err }
+#[wasm_bindgen]
+pub fn example_source(name: &str) -> Option<String> {
+    examples::find(name).map(|example| example.source.to_owned())
 }
 
-struct ExecutionResult {
-    output: String,
-    err: Option<ExecErrorType>,
+// Print the program output and the echoed value to stdout and the diagnostics to stderr.
+// The output is printed as the program runs, so long running scripts show their progress.
+fn run_print_stdout(lox: &mut lox::Lox, source: String) -> Option<ExecErrorType> {
+    let mut echo = String::new();
+    let mut diagnostics = String::new();
+    let err = run_with_output(lox, source, &mut Stdout, &mut echo, &mut diagnostics);
+    if matches!(err, Some(ExecErrorType::OutputFailed)) {
+        return err;
+    }
+    let mut stdout = io::stdout();
+    if stdout
+        .write_all(echo.as_bytes())
+        .and_then(|_| stdout.flush())
+        .is_err()
+    {
+        return Some(ExecErrorType::OutputFailed);
+    }
+    eprint!("{}", diagnostics);
+    err
+}
+
+struct Stdout;
+
+impl fmt::Write for Stdout {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        io::stdout().write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
 }
 
 // Execute the source and write to the output.
+// The value of a lone expression is written to the echo, so callers can send it elsewhere.
 // Return type of error if there was any.
-// The error is already printed in the output.
-fn run_with_output(source: String, output: &mut dyn fmt::Write) -> Option<ExecErrorType> {
-    let lox = lox::Lox::new();
-    match lox.run(source) {
-        Ok(value) => {
-            writeln!(output, "{}", value).unwrap();
+// The error is already printed in the diagnostics.
+fn run_with_output(
+    lox: &mut lox::Lox,
+    source: String,
+    output: &mut dyn fmt::Write,
+    echo: &mut dyn fmt::Write,
+    diagnostics: &mut dyn fmt::Write,
+) -> Option<ExecErrorType> {
+    let result = lox.run(source, output);
+    for warning in lox.take_warnings() {
+        error::report(warning, diagnostics);
+    }
+    match result {
+        Ok(Some(value)) => {
+            writeln!(echo, "{}", value.repr()).unwrap();
             None
         }
+        Ok(None) => None,
         Err(e) => match e {
+            // Nobody is left to read the output, so there is nothing to report.
+            lox::Error::Runtime(error::RuntimeError::OutputFailed) => {
+                Some(ExecErrorType::OutputFailed)
+            }
             lox::Error::Runtime(e) => {
-                error::report(e, output);
+                error::report(e, diagnostics);
                 Some(ExecErrorType::RuntimeError)
             }
             _ => {
-                error::report(e, output);
+                error::report(e, diagnostics);
                 Some(ExecErrorType::GeneralError)
             }
         },
@@ -100,4 +300,15 @@ fn run_with_output(source: String, output: &mut dyn fmt::Write) -> Option<ExecEr
 enum ExecErrorType {
     RuntimeError,
     GeneralError,
+    OutputFailed,
+}
+
+impl ExecErrorType {
+    fn exit_code(&self) -> i32 {
+        match self {
+            ExecErrorType::RuntimeError => EXIT_SOFTWARE,
+            ExecErrorType::GeneralError => EXIT_DATA_ERROR,
+            ExecErrorType::OutputFailed => EXIT_IO_ERROR,
+        }
+    }
 }
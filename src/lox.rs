@@ -1,34 +1,206 @@
-use super::{error, expression::pretty_print, interpreter, parser, scanner, value::Value};
+use super::{
+    diff, environment, error, highlight, interpreter,
+    parser::{self, Input},
+    scanner,
+    statement::{pretty_print, rpn_print, Statement},
+    token::{self, Token},
+    value::Value,
+};
 use std::fmt;
 
 pub struct Lox {
     scanner: scanner::Scanner,
     interpreter: interpreter::Interpreter,
+    limits: parser::Limits,
+    tokens: Vec<Token>,
+    statements: Vec<Statement>,
 }
 
 impl Lox {
     pub fn new() -> Self {
-        let scanner = scanner::Scanner::new();
-        let interpreter = interpreter::Interpreter::new();
+        LoxBuilder::new().build()
+    }
+
+    pub fn builder() -> LoxBuilder {
+        LoxBuilder::new()
+    }
+
+    // Run the source writing printed values to the output.
+    // A source that is a lone expression is evaluated and its value returned.
+    pub fn run(
+        &mut self,
+        source: String,
+        output: &mut dyn fmt::Write,
+    ) -> Result<Option<Value>, Error> {
+        self.scanner.scan_tokens_into(source, &mut self.tokens)?;
+        let mut parser = parser::Parser::new(std::mem::take(&mut self.tokens))
+            .limits(self.limits)
+            .statement_buffer(std::mem::take(&mut self.statements));
+        let input = parser.parse_input();
+        self.tokens = parser.into_buffer();
+        let result = match input? {
+            Input::Program(statements) => {
+                let result = self.interpreter.interpret(&statements, output);
+                // Keep the allocation for the next run.
+                self.statements = statements;
+                self.statements.clear();
+                result.map(|_| None)
+            }
+            Input::Expression(expr) => self.interpreter.evaluate(&expr).map(Some),
+        };
+        result.map_err(|e| e.into())
+    }
+
+    // Execution counters accumulated over all the runs.
+    pub fn statistics(&self) -> interpreter::Statistics {
+        self.interpreter.statistics()
+    }
+
+    // The global variables defined by the previous runs, sorted by name.
+    pub fn globals(&self) -> impl Iterator<Item = (String, Value)> {
+        self.interpreter.globals().into_iter()
+    }
+
+    // Copy the global variables so they can be restored later.
+    pub fn snapshot(&self) -> environment::Snapshot {
+        self.interpreter.snapshot_globals()
+    }
+
+    // Bring the global variables back to the ones of the snapshot.
+    pub fn restore(&self, snapshot: environment::Snapshot) {
+        self.interpreter.restore_globals(snapshot);
+    }
+
+    // Take the warnings reported by the previous runs.
+    pub fn take_warnings(&self) -> Vec<error::Warning> {
+        self.interpreter.take_warnings()
+    }
+
+    // Structurally compare the syntax trees of two sources.
+    pub fn diff_ast(&self, left: String, right: String) -> Result<Vec<diff::Difference>, Error> {
+        let left = parser::parse_program(self.scanner.scan_tokens(left)?, self.limits)?;
+        let right = parser::parse_program(self.scanner.scan_tokens(right)?, self.limits)?;
+        Ok(diff::diff(&left, &right))
+    }
+
+    // Dump the syntax tree along with the syntax errors, if any.
+    // Parts of the source that failed to parse are dumped as error nodes.
+    pub fn dump_ast(
+        &self,
+        source: String,
+        format: AstFormat,
+    ) -> Result<(String, Vec<Error>), Error> {
+        let tokens = self.scanner.scan_tokens(source)?;
+        let (statements, errors) = parser::parse_program_recovering(tokens, self.limits);
+        let dump = match format {
+            AstFormat::Lisp => pretty_print(&statements),
+            AstFormat::Rpn => rpn_print(&statements),
+        };
+        Ok((dump, errors.into_iter().map(Error::from).collect()))
+    }
+
+    // Dump the tokens scanned from the source, one per line.
+    pub fn tokenize(&self, source: String) -> Result<String, Error> {
+        let tokens = self.scanner.scan_tokens(source)?;
+        Ok(token::dump(&tokens))
+    }
+
+    pub fn highlight(&self, source: String, format: HighlightFormat) -> Result<String, Error> {
+        let tokens = self.scanner.scan_tokens(source.clone())?;
+        Ok(highlight::highlight(&source, &tokens, format))
+    }
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct LoxBuilder {
+    strict_equality: bool,
+    warn_float_equality: bool,
+    max_tokens: usize,
+    limits: parser::Limits,
+}
+
+impl LoxBuilder {
+    pub fn new() -> Self {
+        LoxBuilder {
+            strict_equality: false,
+            warn_float_equality: false,
+            max_tokens: scanner::DEFAULT_MAX_TOKENS,
+            limits: parser::Limits::default(),
+        }
+    }
+
+    // Reject sources with more tokens than the limit.
+    pub fn max_tokens(mut self, limit: usize) -> Self {
+        self.max_tokens = limit;
+        self
+    }
+
+    // Reject sources with more statements and expressions than the limit.
+    pub fn max_nodes(mut self, limit: usize) -> Self {
+        self.limits.max_nodes = limit;
+        self
+    }
+
+    // Reject sources nesting statements and expressions deeper than the limit.
+    pub fn max_depth(mut self, limit: usize) -> Self {
+        self.limits.max_depth = limit;
+        self
+    }
+
+    // Reject statements with more binary and logical operators than the limit.
+    pub fn max_operators(mut self, limit: usize) -> Self {
+        self.limits.max_operators = limit;
+        self
+    }
+
+    // Make `==` and `!=` between values of different types a runtime error.
+    pub fn strict_equality(mut self, enabled: bool) -> Self {
+        self.strict_equality = enabled;
+        self
+    }
+
+    // Warn when `==` or `!=` compares two non-integral numbers.
+    pub fn warn_float_equality(mut self, enabled: bool) -> Self {
+        self.warn_float_equality = enabled;
+        self
+    }
+
+    pub fn build(self) -> Lox {
+        let scanner = scanner::Scanner::new().max_tokens(self.max_tokens);
+        let interpreter = interpreter::Interpreter::new()
+            .strict_equality(self.strict_equality)
+            .warn_float_equality(self.warn_float_equality);
         Lox {
             scanner,
             interpreter,
+            limits: self.limits,
+            tokens: Vec::new(),
+            statements: Vec::new(),
         }
     }
+}
 
-    pub fn run(&self, source: String) -> Result<Value, Error> {
-        let tokens = self.scanner.scan_tokens(source)?;
-        let expression = parser::parse(tokens)?;
-        self.interpreter
-            .interpret(&expression)
-            .map_err(|e| e.into())
+impl Default for LoxBuilder {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    pub fn dump_ast(&self, source: String) -> Result<String, Error> {
-        let tokens = self.scanner.scan_tokens(source)?;
-        let expression = parser::parse(tokens)?;
-        Ok(pretty_print(&expression))
-    }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AstFormat {
+    Lisp,
+    Rpn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HighlightFormat {
+    Html,
+    Ansi,
 }
 
 #[derive(Debug, PartialEq)]
@@ -70,10 +242,444 @@ impl fmt::Display for Error {
 mod tests {
     use super::*;
 
+    fn run(lox: &mut Lox, source: &str) -> Result<Option<Value>, Error> {
+        let mut output = String::new();
+        lox.run(source.to_string(), &mut output)
+    }
+
     #[test]
     fn test_run_expression_calculator() {
+        let mut lox = Lox::new();
+        let result = run(&mut lox, "1 - (2 * 3) < 4 == true");
+        assert_eq!(result, Ok(Some(Value::Boolean(true))));
+    }
+
+    #[test]
+    fn test_run_program() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let result = lox.run(
+            "print 1 + 2;\n2 * 3;\nprint \"a\" + \"b\";".to_string(),
+            &mut output,
+        );
+        assert_eq!(result, Ok(None));
+        assert_eq!("3\nab\n", output);
+    }
+
+    #[test]
+    fn test_run_program_output_before_runtime_error() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let result = lox.run("print 1;\nprint -nil;\nprint 2;".to_string(), &mut output);
+        assert!(matches!(result, Err(Error::Runtime(_))));
+        assert_eq!("1\n", output);
+    }
+
+    #[test]
+    fn test_run_variables() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let result = lox.run(
+            "var a = 1;\nvar b;\nprint a;\nprint b;\nb = a + 1;\nprint b;".to_string(),
+            &mut output,
+        );
+        assert_eq!(result, Ok(None));
+        assert_eq!("1\nnil\n2\n", output);
+
+        // variables outlive a single run
+        assert_eq!(run(&mut lox, "a + b"), Ok(Some(Value::Number(3.0))));
+        assert!(matches!(
+            run(&mut lox, "c"),
+            Err(Error::Runtime(
+                error::RuntimeError::UndefinedVariable { .. }
+            ))
+        ));
+        assert_eq!(
+            "[line 2] Error: undefined variable 'c'",
+            run(&mut lox, "a = 1;\nc = 2;").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_run_block() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source = "var a = \"global\";\n{\n  var a = \"outer\";\n  {\n    a = \"inner\";\n    print a;\n  }\n  print a;\n}\nprint a;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("inner\ninner\nglobal\n", output);
+    }
+
+    #[test]
+    fn test_run_logical() {
+        let mut lox = Lox::new();
+        assert_eq!(run(&mut lox, "nil or 2"), Ok(Some(Value::Number(2.0))));
+        assert_eq!(run(&mut lox, "1 or 2"), Ok(Some(Value::Number(1.0))));
+        assert_eq!(run(&mut lox, "nil and 2"), Ok(Some(Value::Nil)));
+        assert_eq!(run(&mut lox, "1 and 2"), Ok(Some(Value::Number(2.0))));
+
+        // the right operand is not evaluated when the left one decides
+        assert_eq!(
+            run(&mut lox, "true or undefined"),
+            Ok(Some(Value::Boolean(true)))
+        );
+        assert_eq!(
+            run(&mut lox, "false and undefined"),
+            Ok(Some(Value::Boolean(false)))
+        );
+        assert!(run(&mut lox, "false or undefined").is_err());
+    }
+
+    #[test]
+    fn test_run_while() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source =
+            "var i = 0;\nwhile (i < 3) {\n  print i;\n  i = i + 1;\n}\nwhile (nil) print i;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("0\n1\n2\n", output);
+    }
+
+    #[test]
+    fn test_run_for() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source = "var i = \"outer\";\nfor (var i = 0; i < 3; i = i + 1) print i;\nprint i;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("0\n1\n2\nouter\n", output);
+    }
+
+    #[test]
+    fn test_run_classes() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source = "class Point {}\nvar p = Point();\np.x = 1;\np.y = p.x + 1;\nprint p.y;\nprint p;\nprint Point;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("2\nPoint instance\nPoint\n", output);
+
+        assert_eq!(run(&mut lox, "p == p"), Ok(Some(Value::Boolean(true))));
+        assert_eq!(
+            run(&mut lox, "p == Point()"),
+            Ok(Some(Value::Boolean(false)))
+        );
+
+        let runtime_error = |result| match result {
+            Err(Error::Runtime(e)) => e.to_string(),
+            _ => panic!("runtime error expected"),
+        };
+        assert_eq!(
+            "[line 1] Error: undefined property 'z'",
+            runtime_error(run(&mut lox, "p.z"))
+        );
+        assert_eq!(
+            "[line 1] Error: only instances have properties",
+            runtime_error(run(&mut lox, "Point.x"))
+        );
+        assert_eq!(
+            "[line 2] Error: attempted to get property 'x' of nil",
+            runtime_error(run(&mut lox, "p.next = nil;\nprint p.next.x;"))
+        );
+        assert_eq!(
+            "[line 2] Error: attempted to set property 'x' of nil",
+            runtime_error(run(&mut lox, "var n;\nn.x = 1;"))
+        );
+        assert_eq!(
+            "[line 1] Error: only instances have fields",
+            runtime_error(run(&mut lox, "1 .x = 2"))
+        );
+        assert_eq!(
+            "[line 1] Error: can only call functions and classes",
+            runtime_error(run(&mut lox, "\"Point\"()"))
+        );
+        assert_eq!(
+            "[line 1] Error: expected 0 arguments but got 2",
+            runtime_error(run(&mut lox, "Point(1, 2)"))
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut lox = Lox::builder().max_tokens(4).build();
+        assert!(run(&mut lox, "1 + 2").is_ok());
+        assert_eq!(
+            "[line 1] Error: program too large, the limit is 4 tokens",
+            run(&mut lox, "1 + 2;").unwrap_err().to_string()
+        );
+
+        let mut lox = Lox::builder().max_nodes(3).build();
+        assert!(run(&mut lox, "1 + 2").is_ok());
+        assert_eq!(
+            "[line 1] Error: program too large, the limit is 3 nodes",
+            run(&mut lox, "1 + 2 + 3").unwrap_err().to_string()
+        );
+
+        let mut lox = Lox::builder().max_depth(3).build();
+        assert!(run(&mut lox, "((1))").is_ok());
+        assert_eq!(
+            "[line 1] Error: nesting too deep, the limit is 3 levels",
+            run(&mut lox, "(((1)))").unwrap_err().to_string()
+        );
+        assert!(run(&mut lox, "{ print 1; }").is_ok());
+        assert!(run(&mut lox, "{ { print 1; } }").is_err());
+        assert!(run(&mut lox, "1 + 1 + 1 + 1 + 1").is_ok());
+
+        let mut lox = Lox::builder().max_operators(2).build();
+        assert!(run(&mut lox, "1 + 2 * 3").is_ok());
+        assert_eq!(
+            "[line 1] Error: statement too long, the limit is 2 operators",
+            run(&mut lox, "1 + 2 * 3 or 4").unwrap_err().to_string()
+        );
+        assert!(run(&mut lox, "{ print 1 + 2 * 3; print 1 + 2 * 3; }").is_ok());
+    }
+
+    // The default limits must fit the 8 MiB stack of the main thread in a debug build.
+    #[test]
+    fn test_default_depth_limit_fits_the_stack() {
+        let test = || {
+            let mut lox = Lox::new();
+            let depth = parser::Limits::default().max_depth;
+            // The deepest source allowed, then one level deeper.
+            let sources = |depth: usize| {
+                [
+                    format!("{}1{}", "(".repeat(depth - 1), ")".repeat(depth - 1)),
+                    format!("{}print 1;{}", "{".repeat(depth - 2), "}".repeat(depth - 2)),
+                    format!("{}1", "- ".repeat(depth - 1)),
+                    format!("nil{}", ".a".repeat(depth - 1)),
+                ]
+            };
+            for source in sources(depth) {
+                assert!(!matches!(
+                    run(&mut lox, &source),
+                    Err(Error::Parse(parser::Error::TooDeeplyNested { .. }))
+                ));
+            }
+            for source in sources(depth + 1) {
+                assert!(matches!(
+                    run(&mut lox, &source),
+                    Err(Error::Parse(parser::Error::TooDeeplyNested { .. }))
+                ));
+            }
+            assert!(matches!(
+                run(&mut lox, &"(".repeat(100_000)),
+                Err(Error::Parse(parser::Error::TooDeeplyNested { .. }))
+            ));
+
+            // Operator chains are bounded separately, the deepest nesting can hold the longest one.
+            let operators = parser::Limits::default().max_operators;
+            let chain = |operators: usize| {
+                format!(
+                    "{}1{}{}",
+                    "(".repeat(depth - 2),
+                    "+1".repeat(operators),
+                    ")".repeat(depth - 2)
+                )
+            };
+            let source = chain(operators);
+            assert_eq!(
+                run(&mut lox, &source),
+                Ok(Some(Value::Number(operators as f64 + 1.0)))
+            );
+            let statement = format!("{};", source);
+            assert!(lox.dump_ast(statement.clone(), AstFormat::Lisp).is_ok());
+            assert!(lox.dump_ast(statement.clone(), AstFormat::Rpn).is_ok());
+            assert!(lox.diff_ast(statement.clone(), statement).is_ok());
+            assert!(matches!(
+                run(&mut lox, &chain(operators + 1)),
+                Err(Error::Parse(parser::Error::TooManyOperators { .. }))
+            ));
+            assert!(matches!(
+                run(&mut lox, &"1+".repeat(100_000)),
+                Err(Error::Parse(parser::Error::TooManyOperators { .. }))
+            ));
+        };
+        std::thread::Builder::new()
+            .stack_size(8 << 20)
+            .spawn(test)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_strict_equality() {
+        let mut lox = Lox::new();
+        assert_eq!(run(&mut lox, "1 == \"1\""), Ok(Some(Value::Boolean(false))));
+
+        let mut lox = Lox::builder().strict_equality(true).build();
+        assert!(matches!(
+            run(&mut lox, "1 == \"1\""),
+            Err(Error::Runtime(
+                error::RuntimeError::OperandsMustHaveSameType { .. }
+            ))
+        ));
+        assert_eq!(run(&mut lox, "1 == 1"), Ok(Some(Value::Boolean(true))));
+    }
+
+    #[test]
+    fn test_float_equality_warning() {
+        let mut lox = Lox::builder().warn_float_equality(true).build();
+        assert_eq!(
+            run(&mut lox, "0.1 + 0.2 == 0.3"),
+            Ok(Some(Value::Boolean(false)))
+        );
+        let warnings = lox.take_warnings();
+        assert_eq!(1, warnings.len());
+        assert_eq!(
+            "[line 1] Warning: '==' between non-integral numbers is unreliable, compare the difference against a tolerance instead",
+            warnings[0].to_string()
+        );
+
+        let mut lox = Lox::new();
+        assert!(run(&mut lox, "0.1 + 0.2 == 0.3").is_ok());
+        assert!(lox.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_float_equality_warning_once_per_operator() {
+        let mut lox = Lox::builder().warn_float_equality(true).build();
+        let source = "for (var i = 0; i < 5; i = i + 1) {\n  i + 0.5 == 0.1;\n  0.1 != i + 0.5;\n}";
+        assert_eq!(run(&mut lox, source), Ok(None));
+        let lines: Vec<_> = lox
+            .take_warnings()
+            .iter()
+            .map(|warning| warning.to_string()[..8].to_owned())
+            .collect();
+        assert_eq!(vec!["[line 2]", "[line 3]"], lines);
+
+        // A new run warns again.
+        assert!(run(&mut lox, "0.1 == 0.3").is_ok());
+        assert_eq!(1, lox.take_warnings().len());
+    }
+
+    #[test]
+    fn test_dump_ast_formats() {
+        let lox = Lox::new();
+        let source = "print (1 + 2) * 3;".to_string();
+        assert_eq!(
+            lox.dump_ast(source.clone(), AstFormat::Lisp),
+            Ok(("(print (* (group (+ 1 2)) 3))".to_owned(), vec![]))
+        );
+        assert_eq!(
+            lox.dump_ast(source, AstFormat::Rpn),
+            Ok(("1 2 + 3 * print".to_owned(), vec![]))
+        );
+    }
+
+    #[test]
+    fn test_dump_ast_with_syntax_errors() {
+        let lox = Lox::new();
+        let source = "print 1 -;\nprint 2;".to_string();
+        assert_eq!(
+            lox.dump_ast(source, AstFormat::Rpn),
+            Ok((
+                "1 error - print\n2 print".to_owned(),
+                vec![Error::Parse(parser::Error::ExpressionExpected { line: 1 })]
+            ))
+        );
+        assert!(lox.dump_ast("\"".to_string(), AstFormat::Lisp).is_err());
+    }
+
+    #[test]
+    fn test_diff_ast() {
         let lox = Lox::new();
-        let result = lox.run("1 - (2 * 3) < 4 == true".to_string());
-        assert_eq!(result, Ok(Value::Boolean(true)));
+        let differences = lox
+            .diff_ast("1 + 2;".to_string(), "1 +\n2;".to_string())
+            .unwrap();
+        assert!(differences.is_empty());
+
+        let differences = lox
+            .diff_ast("1 + 2;".to_string(), "1 + 3;".to_string())
+            .unwrap();
+        assert_eq!(1, differences.len());
+        assert!(lox.diff_ast("1 +;".to_string(), "1;".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_run_reuses_lox() {
+        let mut lox = Lox::new();
+        assert_eq!(run(&mut lox, "1 + 2"), Ok(Some(Value::Number(3.0))));
+        assert!(run(&mut lox, "(").is_err());
+        assert_eq!(
+            run(&mut lox, "\"a\" + \"b\""),
+            Ok(Some(Value::String("ab".to_owned())))
+        );
+
+        run(&mut lox, "var a = 1; a = 2; a = 3;").unwrap();
+        assert!(lox.statements.is_empty());
+        assert!(lox.statements.capacity() >= 3);
+        assert!(lox.tokens.capacity() >= 12);
+    }
+
+    #[test]
+    fn test_run_increment() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source = "var i = 1;\nprint ++i;\nprint --i - 1;\nclass A {}\nvar a = A();\na.n = 0;\n++a.n;\nprint a.n;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("2\n0\n1\n", output);
+        assert_eq!(run(&mut lox, "i"), Ok(Some(Value::Number(1.0))));
+    }
+
+    #[test]
+    fn test_run_subtract_negation() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        let source = "print 1--1;\nvar a = 2;\nprint a--a * 3;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("2\n8\n", output);
+        assert_eq!(run(&mut lox, "1--1"), Ok(Some(Value::Number(2.0))));
+    }
+
+    #[test]
+    fn test_increment_property_evaluates_object_once() {
+        let mut lox = Lox::new();
+        let mut output = String::new();
+        // Evaluating the object twice would flip `odd` back and read one instance while writing the other.
+        let source = "class A {}\nvar a = A();\na.n = 1;\nvar b = A();\nb.n = 10;\nvar odd = false;\nprint ++((odd = !odd) and a or b).n;\nprint --(a).n;\nprint odd;\nprint b.n;";
+        assert_eq!(Ok(None), lox.run(source.to_string(), &mut output));
+        assert_eq!("2\n1\ntrue\n10\n", output);
+        assert!(matches!(
+            lox.run("++A().n;".to_string(), &mut output),
+            Err(Error::Runtime(
+                error::RuntimeError::UndefinedProperty { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_drop_long_instance_list() {
+        let mut lox = Lox::new();
+        let source = "class Node {}\nvar head = nil;\nfor (var i = 0; i < 100000; i = i + 1) { var n = Node(); n.next = head; head = n; }";
+        assert_eq!(run(&mut lox, source), Ok(None));
+        assert_eq!(run(&mut lox, "head = nil"), Ok(Some(Value::Nil)));
+
+        // The list is also freed when still alive as the interpreter goes away.
+        run(&mut lox, source).unwrap();
+        drop(lox);
+    }
+
+    #[test]
+    fn test_globals() {
+        let mut lox = Lox::new();
+        assert_eq!(0, lox.globals().count());
+        run(&mut lox, "var b = 1; var a; { var c = 2; }").unwrap();
+        assert_eq!(
+            vec![
+                ("a".to_owned(), Value::Nil),
+                ("b".to_owned(), Value::Number(1.0))
+            ],
+            lox.globals().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut lox = Lox::new();
+        run(&mut lox, "var a = 1;").unwrap();
+        let snapshot = lox.snapshot();
+        run(&mut lox, "a = 2; var b = 3;").unwrap();
+
+        lox.restore(snapshot);
+        assert_eq!(run(&mut lox, "a"), Ok(Some(Value::Number(1.0))));
+        assert!(run(&mut lox, "b").is_err());
     }
 }
@@ -1,4 +1,7 @@
-use relox::{dump_file_ast, run_file, run_prompt};
+use relox::{
+    diff_file_ast, dump_file_ast, highlight_file, print_grammar, run_example, run_file, run_prompt,
+    tokenize_file, AstFormat, Encoding, HighlightFormat, LoxBuilder, EXIT_USAGE,
+};
 use std::env;
 
 fn main() {
@@ -9,23 +12,118 @@ fn main() {
 
     let command = args.nth(1).unwrap();
     match command.as_str() {
-        "run" => match args.next() {
-            None => run_prompt(),
-            Some(file) => run_file(file),
-        },
+        "run" => {
+            let mut builder = LoxBuilder::new();
+            let mut encoding = Encoding::Utf8;
+            let mut summary = false;
+            let mut file = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--warn-float-eq" => builder = builder.warn_float_equality(true),
+                    "--summary" => summary = true,
+                    _ if arg.starts_with("--encoding=") => encoding = parse_encoding(&arg),
+                    _ if arg.starts_with("--") => print_help_and_exit(),
+                    _ => file = Some(arg),
+                }
+            }
+            match file {
+                None => run_prompt(builder),
+                Some(file) => run_file(file, builder, encoding, summary),
+            }
+        }
         "ast" => {
-            let file = args.next().unwrap();
-            dump_file_ast(file)
+            let mut format = AstFormat::Lisp;
+            let mut encoding = Encoding::Utf8;
+            let mut file = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--format=lisp" => format = AstFormat::Lisp,
+                    "--format=rpn" => format = AstFormat::Rpn,
+                    _ if arg.starts_with("--encoding=") => encoding = parse_encoding(&arg),
+                    _ if arg.starts_with("--") => print_help_and_exit(),
+                    _ => file = Some(arg),
+                }
+            }
+            match file {
+                Some(file) => dump_file_ast(file, format, encoding),
+                None => print_help_and_exit(),
+            }
+        }
+        "tokenize" => {
+            let mut encoding = Encoding::Utf8;
+            let mut file = None;
+            for arg in args {
+                match arg.as_str() {
+                    _ if arg.starts_with("--encoding=") => encoding = parse_encoding(&arg),
+                    _ if arg.starts_with("--") => print_help_and_exit(),
+                    _ => file = Some(arg),
+                }
+            }
+            match file {
+                Some(file) => tokenize_file(file, encoding),
+                None => print_help_and_exit(),
+            }
+        }
+        "highlight" => {
+            let mut format = HighlightFormat::Html;
+            let mut encoding = Encoding::Utf8;
+            let mut file = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--format=html" => format = HighlightFormat::Html,
+                    "--format=ansi" => format = HighlightFormat::Ansi,
+                    _ if arg.starts_with("--encoding=") => encoding = parse_encoding(&arg),
+                    _ if arg.starts_with("--") => print_help_and_exit(),
+                    _ => file = Some(arg),
+                }
+            }
+            match file {
+                Some(file) => highlight_file(file, format, encoding),
+                None => print_help_and_exit(),
+            }
+        }
+        "diff-ast" => {
+            let mut encoding = Encoding::Utf8;
+            let mut files = Vec::new();
+            for arg in args {
+                match arg.as_str() {
+                    _ if arg.starts_with("--encoding=") => encoding = parse_encoding(&arg),
+                    _ if arg.starts_with("--") => print_help_and_exit(),
+                    _ => files.push(arg),
+                }
+            }
+            match <[String; 2]>::try_from(files) {
+                Ok([left, right]) => diff_file_ast(left, right, encoding),
+                Err(_) => print_help_and_exit(),
+            }
         }
+        "examples" => match args.next() {
+            Some(arg) if arg.starts_with("--") => print_help_and_exit(),
+            name => run_example(name),
+        },
+        "grammar" => print_grammar(),
+        _ => print_help_and_exit(),
+    }
+}
+
+fn parse_encoding(arg: &str) -> Encoding {
+    match arg.strip_prefix("--encoding=") {
+        Some("utf-8") => Encoding::Utf8,
+        Some("latin1") => Encoding::Latin1,
         _ => print_help_and_exit(),
     }
 }
 
 fn print_help_and_exit() -> ! {
-    println!(
+    eprintln!(
         "Usage: 
-    lox run [script]
-    lox ast <script>"
+    lox run [--warn-float-eq] [--summary] [--encoding=utf-8|latin1] [script]
+    lox ast [--format=lisp|rpn] [--encoding=utf-8|latin1] <script>
+    lox tokenize [--encoding=utf-8|latin1] <script>
+    lox highlight [--format=html|ansi] [--encoding=utf-8|latin1] <script>
+    lox diff-ast [--encoding=utf-8|latin1] <script> <script>
+    lox examples [name]
+    lox grammar"
     );
-    std::process::exit(64);
+    std::process::exit(EXIT_USAGE);
 }
@@ -1,207 +1,1080 @@
 use super::{
     error::format_error,
     expression::Expression,
-    token::{Token, TokenType},
+    statement::Statement,
+    token::{Literal, Token, TokenType},
 };
 use std::fmt;
 
-pub fn parse(tokens: Vec<Token>) -> Result {
-    let mut reader = Reader::new(tokens);
-    parse_with_reader(&mut reader)
+// Parse the whole program, stopping at the first syntax error.
+// The tokens must end with an `Eof` token, as the scanner produces them.
+pub fn parse_program(tokens: Vec<Token>, limits: Limits) -> ProgramResult {
+    Parser::new(tokens).limits(limits).parse_program()
 }
 
-fn parse_with_reader(reader: &mut Reader) -> Result {
-    let result = expression(reader);
-    if result.is_err() {
-        syncronize(reader);
-    }
+// Parse the whole program even if it has syntax errors.
+// Broken expressions are replaced by `Expression::Error` nodes, and broken statements by
+// expression statements holding one, so the tree keeps the shape and lines of the source.
+pub fn parse_program_recovering(
+    tokens: Vec<Token>,
+    limits: Limits,
+) -> (Vec<Statement>, Vec<Error>) {
+    Parser::new(tokens)
+        .limits(limits)
+        .parse_program_recovering()
+}
+
+// Parse interactive input, either a program or a lone expression, draining the token buffer.
+// The emptied buffer keeps its allocation so it can be reused by the caller.
+pub fn parse_tokens(tokens: &mut Vec<Token>, limits: Limits) -> std::result::Result<Input, Error> {
+    let mut parser = Parser::new(std::mem::take(tokens)).limits(limits);
+    let result = parser.parse_input();
+    *tokens = parser.into_buffer();
     result
 }
 
 type Result = std::result::Result<Expression, Error>;
-
-fn expression(reader: &mut Reader) -> Result {
-    equality(reader)
+type StatementResult = std::result::Result<Statement, Error>;
+type ProgramResult = std::result::Result<Vec<Statement>, Error>;
+
+// Interactive input is either a program or a single expression whose value is echoed.
+#[derive(Debug)]
+pub enum Input {
+    Program(Vec<Statement>),
+    Expression(Expression),
 }
 
-fn equality(reader: &mut Reader) -> Result {
-    let mut expr = comparsion(reader)?;
+// Caps on the size of the syntax tree, so adversarial input can't exhaust memory or the stack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    // Number of statements and expressions written in the source.
+    pub max_nodes: usize,
+    // Nesting of statements and expressions, which the parser and interpreter handle recursively.
+    pub max_depth: usize,
+    // Binary and logical operators in one statement. Chains like `1 + 2 + 3` nest down the left
+    // operand without the parser recursing, so they don't count toward the depth.
+    pub max_operators: usize,
+}
 
-    while let Some(TokenType::BangEqual) | Some(TokenType::EqualEqual) = reader.peek_type() {
-        let operator = reader.advance().unwrap();
-        let right = comparsion(reader)?;
-        expr = Expression::Binary {
-            left: Box::new(expr),
-            operator,
-            right: Box::new(right),
-        };
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 1_000_000,
+            max_depth: 128,
+            max_operators: 4096,
+        }
     }
+}
 
-    Ok(expr)
+// A grammar rule in EBNF notation.
+pub struct Rule {
+    pub name: &'static str,
+    pub production: &'static str,
 }
 
-fn comparsion(reader: &mut Reader) -> Result {
-    let mut expr = term(reader)?;
-
-    while let Some(TokenType::Greater)
-    | Some(TokenType::GreaterEqual)
-    | Some(TokenType::Less)
-    | Some(TokenType::LessEqual) = reader.peek_type()
-    {
-        let operator = reader.advance().unwrap();
-        let right = term(reader)?;
-        expr = Expression::Binary {
-            left: Box::new(expr),
-            operator,
-            right: Box::new(right),
+// The grammar accepted by the parser.
+// Keep it in sync with the parsing methods below, one rule per method.
+pub const GRAMMAR: &[Rule] = &[
+    Rule {
+        name: "program",
+        production: "{ declaration } , EOF",
+    },
+    Rule {
+        name: "declaration",
+        production: "classDecl | varDecl | statement",
+    },
+    Rule {
+        name: "classDecl",
+        production: "\"class\" , IDENTIFIER , \"{\" , \"}\"",
+    },
+    Rule {
+        name: "varDecl",
+        production: "\"var\" , IDENTIFIER , [ \"=\" , expression ] , \";\"",
+    },
+    Rule {
+        name: "statement",
+        production: "exprStmt | forStmt | printStmt | whileStmt | block",
+    },
+    Rule {
+        name: "exprStmt",
+        production: "expression , \";\"",
+    },
+    Rule {
+        name: "printStmt",
+        production: "\"print\" , expression , \";\"",
+    },
+    Rule {
+        name: "forStmt",
+        production: "\"for\" , \"(\" , ( varDecl | exprStmt | \";\" ) , [ expression ] , \";\" , [ expression ] , \")\" , statement",
+    },
+    Rule {
+        name: "whileStmt",
+        production: "\"while\" , \"(\" , expression , \")\" , statement",
+    },
+    Rule {
+        name: "block",
+        production: "\"{\" , { declaration } , \"}\"",
+    },
+    Rule {
+        name: "expression",
+        production: "assignment",
+    },
+    Rule {
+        name: "assignment",
+        production: "[ call , \".\" ] , IDENTIFIER , \"=\" , assignment | logicOr",
+    },
+    Rule {
+        name: "logicOr",
+        production: "logicAnd , { \"or\" , logicAnd }",
+    },
+    Rule {
+        name: "logicAnd",
+        production: "equality , { \"and\" , equality }",
+    },
+    Rule {
+        name: "equality",
+        production: "comparison , { ( \"!=\" | \"==\" ) , comparison }",
+    },
+    Rule {
+        name: "comparison",
+        production: "term , { ( \">\" | \">=\" | \"<\" | \"<=\" ) , term }",
+    },
+    Rule {
+        name: "term",
+        production: "factor , { ( \"-\" | \"+\" ) , factor }",
+    },
+    Rule {
+        name: "factor",
+        production: "unary , { ( \"/\" | \"*\" ) , unary }",
+    },
+    Rule {
+        name: "unary",
+        production: "( \"!\" | \"-\" ) , unary | ( \"++\" | \"--\" ) , call | call",
+    },
+    Rule {
+        name: "call",
+        production: "primary , { \"(\" , [ arguments ] , \")\" | \".\" , IDENTIFIER }",
+    },
+    Rule {
+        name: "arguments",
+        production: "expression , { \",\" , expression }",
+    },
+    Rule {
+        name: "primary",
+        production: "NUMBER | STRING | IDENTIFIER | \"true\" | \"false\" | \"nil\" | \"(\" , expression , \")\"",
+    },
+];
+
+// Render the grammar as EBNF, one rule per line with aligned definitions.
+pub fn grammar_ebnf() -> String {
+    let width = GRAMMAR
+        .iter()
+        .map(|rule| rule.name.len())
+        .max()
+        .unwrap_or(0);
+    GRAMMAR
+        .iter()
+        .map(|rule| format!("{:width$} = {} ;\n", rule.name, rule.production))
+        .collect()
+}
+
+// A recursive descent parser over a buffer of tokens.
+// Build it with `new`, optionally cap the tree with `limits`, then call one of the `parse_*` entry points.
+pub struct Parser {
+    reader: Reader,
+    limits: Limits,
+    nodes: usize,
+    depth: usize,
+    // Operators counted so far in the statement being parsed.
+    operators: usize,
+    // When recovering, errors are collected here instead of aborting the parse.
+    recover: bool,
+    errors: Vec<Error>,
+    // Parsed statements are pushed here, so a caller can hand in a buffer to reuse.
+    statements: Vec<Statement>,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            reader: Reader::new(tokens),
+            limits: Limits::default(),
+            nodes: 0,
+            depth: 0,
+            operators: 0,
+            recover: false,
+            errors: Vec::new(),
+            statements: Vec::new(),
+        }
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    // Parse the program into the allocation of a previously returned statement buffer.
+    pub fn statement_buffer(mut self, mut statements: Vec<Statement>) -> Self {
+        statements.clear();
+        self.statements = statements;
+        self
+    }
+
+    // Parse a single expression.
+    // On error the reader is moved to the next statement boundary.
+    pub fn parse_expression(&mut self) -> Result {
+        let result = self.expression();
+        if result.is_err() {
+            self.syncronize();
+        }
+        result
+    }
+
+    // Parse statements up to the end of the tokens.
+    // On error the reader is moved to the next statement boundary.
+    pub fn parse_program(&mut self) -> ProgramResult {
+        let mut statements = std::mem::take(&mut self.statements);
+        while !self.is_at_end() {
+            let result = self.declaration();
+            match result {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    self.syncronize();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(statements)
+    }
+
+    pub fn parse_program_recovering(&mut self) -> (Vec<Statement>, Vec<Error>) {
+        self.recover = true;
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            let remaining = self.reader.remaining();
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    let line = self.reader.line();
+                    statements.push(Statement::Expression {
+                        expr: Expression::Error { line },
+                    });
+                    let limit_reached = matches!(
+                        e,
+                        Error::TooManyNodes { .. }
+                            | Error::TooDeeplyNested { .. }
+                            | Error::TooManyOperators { .. }
+                    );
+                    self.errors.push(e);
+                    if limit_reached {
+                        break;
+                    }
+                    self.syncronize();
+                    // Always make progress, even on an error at a statement keyword.
+                    if self.reader.remaining() == remaining {
+                        self.reader.advance();
+                    }
+                }
+            }
+        }
+        (statements, std::mem::take(&mut self.errors))
+    }
+
+    // Parse statements, unless the input is a lone expression without the closing ';'.
+    pub fn parse_input(&mut self) -> std::result::Result<Input, Error> {
+        let starts_statement = matches!(
+            self.reader.peek_type(),
+            Some(TokenType::Print)
+                | Some(TokenType::Class)
+                | Some(TokenType::Var)
+                | Some(TokenType::For)
+                | Some(TokenType::While)
+                | Some(TokenType::LeftBrace)
+        );
+        if !starts_statement && !self.is_at_end() {
+            let expr = self.parse_expression()?;
+            if self.is_at_end() {
+                return Ok(Input::Expression(expr));
+            }
+            if let Err(e) = self.consume_semicolon() {
+                self.syncronize();
+                return Err(e);
+            }
+            self.statements.push(Statement::Expression { expr });
+            return self.parse_program().map(Input::Program);
+        }
+        self.parse_program().map(Input::Program)
+    }
+
+    // Give back the drained token buffer.
+    pub fn into_buffer(self) -> Vec<Token> {
+        self.reader.into_buffer()
+    }
+
+    fn is_at_end(&mut self) -> bool {
+        matches!(self.reader.peek_type(), Some(TokenType::Eof) | None)
+    }
+
+    fn count_node(&mut self) -> std::result::Result<(), Error> {
+        if self.nodes >= self.limits.max_nodes {
+            return Err(Error::TooManyNodes {
+                line: self.reader.line(),
+                limit: self.limits.max_nodes,
+            });
+        }
+        self.nodes += 1;
+        Ok(())
+    }
+
+    // Count a node that deepens the tree without the parser recursing, like the callees
+    // in `a.b.c()`: the interpreter still recurses through them.
+    fn count_chained_node(&mut self, chain: &mut usize) -> std::result::Result<(), Error> {
+        *chain += 1;
+        if self.depth + *chain > self.limits.max_depth {
+            return Err(Error::TooDeeplyNested {
+                line: self.reader.line(),
+                limit: self.limits.max_depth,
+            });
+        }
+        self.count_node()
+    }
+
+    // Count a binary or logical operator. The interpreter evaluates chains of them in a loop.
+    fn count_operator(&mut self) -> std::result::Result<(), Error> {
+        if self.operators >= self.limits.max_operators {
+            return Err(Error::TooManyOperators {
+                line: self.reader.line(),
+                limit: self.limits.max_operators,
+            });
+        }
+        self.operators += 1;
+        self.count_node()
+    }
+
+    // Run a parsing method one nesting level deeper.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> std::result::Result<T, Error>,
+    ) -> std::result::Result<T, Error> {
+        if self.depth >= self.limits.max_depth {
+            return Err(Error::TooDeeplyNested {
+                line: self.reader.line(),
+                limit: self.limits.max_depth,
+            });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn declaration(&mut self) -> StatementResult {
+        let outer = std::mem::take(&mut self.operators);
+        let result = match self.reader.peek_type() {
+            Some(TokenType::Class) => {
+                self.reader.advance();
+                self.class_declaration()
+            }
+            Some(TokenType::Var) => {
+                self.reader.advance();
+                self.var_declaration()
+            }
+            _ => self.statement(),
         };
+        self.operators = outer;
+        result
     }
 
-    Ok(expr)
-}
+    // Classes have no methods yet, so the body is always empty.
+    fn class_declaration(&mut self) -> StatementResult {
+        let name = match self.reader.peek_type() {
+            Some(TokenType::Identifier) => self.reader.advance().unwrap(),
+            _ => {
+                return Err(Error::ClassNameExpected {
+                    line: self.reader.line(),
+                })
+            }
+        };
+
+        let brace = match self.reader.peek_type() {
+            Some(TokenType::LeftBrace) => self.reader.advance().unwrap(),
+            _ => {
+                return Err(Error::LeftBraceExpected {
+                    line: self.reader.line(),
+                    before: "class body",
+                })
+            }
+        };
+        match self.reader.peek_type() {
+            Some(TokenType::RightBrace) => self.reader.advance(),
+            _ => {
+                return Err(Error::RightBraceExpected {
+                    line: self.reader.line(),
+                    after: "class body",
+                    opened_line: brace.line,
+                })
+            }
+        };
 
-fn term(reader: &mut Reader) -> Result {
-    let mut expr = factor(reader)?;
+        self.count_node()?;
+        Ok(Statement::Class { name })
+    }
 
-    while let Some(TokenType::Minus) | Some(TokenType::Plus) = reader.peek_type() {
-        let operator = reader.advance().unwrap();
-        let right = factor(reader)?;
-        expr = Expression::Binary {
-            left: Box::new(expr),
-            operator,
-            right: Box::new(right),
+    fn var_declaration(&mut self) -> StatementResult {
+        let name = match self.reader.peek_type() {
+            Some(TokenType::Identifier) => self.reader.advance().unwrap(),
+            _ => {
+                return Err(Error::VariableNameExpected {
+                    line: self.reader.line(),
+                })
+            }
+        };
+
+        let initializer = match self.reader.peek_type() {
+            Some(TokenType::Equal) => {
+                self.reader.advance();
+                Some(self.expression()?)
+            }
+            _ => None,
         };
+
+        self.consume_semicolon()?;
+        self.count_node()?;
+        Ok(Statement::Var { name, initializer })
     }
 
-    Ok(expr)
-}
+    fn statement(&mut self) -> StatementResult {
+        self.count_node()?;
+        self.nested(|parser| match parser.reader.peek_type() {
+            Some(TokenType::Print) => {
+                parser.reader.advance();
+                parser.print_statement()
+            }
+            Some(TokenType::For) => {
+                parser.reader.advance();
+                parser.for_statement()
+            }
+            Some(TokenType::While) => {
+                parser.reader.advance();
+                parser.while_statement()
+            }
+            Some(TokenType::LeftBrace) => {
+                let brace = parser.reader.advance().unwrap();
+                parser.block(&brace)
+            }
+            _ => parser.expression_statement(),
+        })
+    }
+
+    fn print_statement(&mut self) -> StatementResult {
+        let expr = self.expression()?;
+        self.consume_semicolon()?;
+        Ok(Statement::Print { expr })
+    }
 
-fn factor(reader: &mut Reader) -> Result {
-    let mut expr = unary(reader)?;
+    // A for loop is desugared into a while loop, so it has no node of its own:
+    // `for (init; cond; incr) body` becomes `{ init; while (cond) { body incr; } }`.
+    fn for_statement(&mut self) -> StatementResult {
+        let paren = self.consume_left_paren("for")?;
 
-    while let Some(TokenType::Slash) | Some(TokenType::Star) = reader.peek_type() {
-        let operator = reader.advance().unwrap();
-        let right = unary(reader)?;
-        expr = Expression::Binary {
-            left: Box::new(expr),
-            operator,
-            right: Box::new(right),
+        let initializer = match self.reader.peek_type() {
+            Some(TokenType::Semicolon) => {
+                self.reader.advance();
+                None
+            }
+            Some(TokenType::Var) => {
+                self.reader.advance();
+                Some(self.var_declaration()?)
+            }
+            _ => Some(self.expression_statement()?),
+        };
+
+        let condition = match self.reader.peek_type() {
+            Some(TokenType::Semicolon) => Expression::Literal {
+                value: Literal::Boolean(true),
+            },
+            _ => self.expression()?,
+        };
+        self.consume_semicolon()?;
+
+        let increment = match self.reader.peek_type() {
+            Some(TokenType::RightParen) => None,
+            _ => Some(self.expression()?),
+        };
+        self.consume_right_paren(&paren)?;
+
+        let mut body = self.statement()?;
+        if let Some(increment) = increment {
+            body = Statement::Block {
+                statements: vec![body, Statement::Expression { expr: increment }],
+            };
+        }
+        body = Statement::While {
+            condition,
+            body: Box::new(body),
         };
+        if let Some(initializer) = initializer {
+            body = Statement::Block {
+                statements: vec![initializer, body],
+            };
+        }
+        Ok(body)
     }
 
-    Ok(expr)
-}
+    fn while_statement(&mut self) -> StatementResult {
+        let paren = self.consume_left_paren("while")?;
+        let condition = self.expression()?;
+        self.consume_right_paren(&paren)?;
+        let body = self.statement()?;
+        Ok(Statement::While {
+            condition,
+            body: Box::new(body),
+        })
+    }
+
+    fn consume_left_paren(&mut self, keyword: &'static str) -> std::result::Result<Token, Error> {
+        match self.reader.peek_type() {
+            Some(TokenType::LeftParen) => Ok(self.reader.advance().unwrap()),
+            _ => Err(Error::LeftParenExpected {
+                line: self.reader.line(),
+                keyword,
+            }),
+        }
+    }
+
+    fn consume_right_paren(&mut self, paren: &Token) -> std::result::Result<(), Error> {
+        match self.reader.peek_type() {
+            Some(TokenType::RightParen) => {
+                self.reader.advance();
+                Ok(())
+            }
+            _ => Err(Error::RightParenExpected {
+                line: self.reader.line(),
+                opened_line: paren.line,
+            }),
+        }
+    }
+
+    fn block(&mut self, brace: &Token) -> StatementResult {
+        let mut statements = Vec::new();
+        while !matches!(self.reader.peek_type(), Some(TokenType::RightBrace)) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        match self.reader.peek_type() {
+            Some(TokenType::RightBrace) => {
+                self.reader.advance();
+                Ok(Statement::Block { statements })
+            }
+            _ => Err(Error::RightBraceExpected {
+                line: self.reader.line(),
+                after: "block",
+                opened_line: brace.line,
+            }),
+        }
+    }
+
+    fn expression_statement(&mut self) -> StatementResult {
+        let expr = self.expression()?;
+        self.consume_semicolon()?;
+        Ok(Statement::Expression { expr })
+    }
+
+    fn consume_semicolon(&mut self) -> std::result::Result<(), Error> {
+        match self.reader.peek_type() {
+            Some(TokenType::Semicolon) => {
+                self.reader.advance();
+                Ok(())
+            }
+            _ => Err(Error::SemicolonExpected {
+                line: self.reader.line(),
+            }),
+        }
+    }
+
+    fn expression(&mut self) -> Result {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result {
+        let expr = self.or()?;
+
+        if let Some(TokenType::Equal) = self.reader.peek_type() {
+            let equals = self.reader.advance().unwrap();
+            let value = self.nested(Self::assignment)?;
+            self.count_node()?;
+
+            return match expr {
+                Expression::Variable { name } => Ok(Expression::Assign {
+                    name,
+                    value: Box::new(value),
+                }),
+                Expression::Get { object, name } => Ok(Expression::Set {
+                    object,
+                    name,
+                    value: Box::new(value),
+                }),
+                error @ Expression::Error { .. } if self.recover => Ok(error),
+                _ => Err(Error::InvalidAssignmentTarget { line: equals.line }),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result {
+        let mut expr = self.and()?;
 
-fn unary(reader: &mut Reader) -> Result {
-    match reader.peek_type() {
-        Some(TokenType::Bang) | Some(TokenType::Minus) => {
-            let operator = reader.advance().unwrap();
-            let right = unary(reader)?;
-            let expr = Expression::Unary {
+        while let Some(TokenType::Or) = self.reader.peek_type() {
+            let operator = self.reader.advance().unwrap();
+            let right = self.and()?;
+            self.count_operator()?;
+            expr = Expression::Logical {
+                left: Box::new(expr),
                 operator,
                 right: Box::new(right),
             };
-            Ok(expr)
         }
-        _ => primary(reader),
+
+        Ok(expr)
     }
-}
 
-fn primary(reader: &mut Reader) -> Result {
-    match reader.peek_type() {
-        Some(TokenType::True)
-        | Some(TokenType::False)
-        | Some(TokenType::Nil)
-        | Some(TokenType::Number)
-        | Some(TokenType::String) => {
-            let token = reader.advance().unwrap();
-            let expr = Expression::Literal {
-                value: token.literal.unwrap(),
+    fn and(&mut self) -> Result {
+        let mut expr = self.equality()?;
+
+        while let Some(TokenType::And) = self.reader.peek_type() {
+            let operator = self.reader.advance().unwrap();
+            let right = self.equality()?;
+            self.count_operator()?;
+            expr = Expression::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result {
+        let mut expr = self.comparsion()?;
+
+        while let Some(TokenType::BangEqual) | Some(TokenType::EqualEqual) = self.reader.peek_type()
+        {
+            let operator = self.reader.advance().unwrap();
+            let right = self.comparsion()?;
+            self.count_operator()?;
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn comparsion(&mut self) -> Result {
+        let mut expr = self.term()?;
+
+        while let Some(TokenType::Greater)
+        | Some(TokenType::GreaterEqual)
+        | Some(TokenType::Less)
+        | Some(TokenType::LessEqual) = self.reader.peek_type()
+        {
+            let operator = self.reader.advance().unwrap();
+            let right = self.term()?;
+            self.count_operator()?;
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result {
+        let mut expr = self.factor()?;
+
+        loop {
+            // After an operand `--` is a subtraction of a negation, as in `1--1`.
+            if let Some(TokenType::MinusMinus) = self.reader.peek_type() {
+                self.reader.split_decrement();
+            }
+            let (Some(TokenType::Minus) | Some(TokenType::Plus)) = self.reader.peek_type() else {
+                break;
+            };
+            let operator = self.reader.advance().unwrap();
+            let right = self.factor()?;
+            self.count_operator()?;
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result {
+        let mut expr = self.unary()?;
+
+        while let Some(TokenType::Slash) | Some(TokenType::Star) = self.reader.peek_type() {
+            let operator = self.reader.advance().unwrap();
+            let right = self.unary()?;
+            self.count_operator()?;
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
             };
-            Ok(expr)
         }
-        Some(TokenType::LeftParen) => {
-            reader.advance();
-            let expr = expression(reader)?;
-            let token_type = reader.advance().map(|x| x.t);
-            if token_type != Some(TokenType::RightParen) {
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result {
+        match self.reader.peek_type() {
+            Some(TokenType::Bang) | Some(TokenType::Minus) => {
+                let operator = self.reader.advance().unwrap();
+                let right = self.nested(Self::unary)?;
+                self.count_node()?;
+                let expr = Expression::Unary {
+                    operator,
+                    right: Box::new(right),
+                };
+                Ok(expr)
+            }
+            Some(TokenType::PlusPlus) | Some(TokenType::MinusMinus) => {
+                let operator = self.reader.advance().unwrap();
+                let target = self.nested(Self::call)?;
+                self.count_node()?;
+                // A missing target was already reported when its placeholder was made.
+                if self.recover && matches!(target, Expression::Error { .. }) {
+                    return Ok(target);
+                }
+                increment(operator, target)
+            }
+            _ => self.call(),
+        }
+    }
+
+    fn call(&mut self) -> Result {
+        let mut expr = self.primary()?;
+
+        let mut chain = 0;
+        loop {
+            match self.reader.peek_type() {
+                Some(TokenType::LeftParen) => {
+                    let paren = self.reader.advance().unwrap();
+                    self.count_chained_node(&mut chain)?;
+                    expr = self.finish_call(expr, paren)?;
+                }
+                Some(TokenType::Dot) => {
+                    self.reader.advance();
+                    let name = match self.reader.peek_type() {
+                        Some(TokenType::Identifier) => self.reader.advance().unwrap(),
+                        _ => {
+                            return Err(Error::PropertyNameExpected {
+                                line: self.reader.line(),
+                            })
+                        }
+                    };
+                    self.count_chained_node(&mut chain)?;
+                    expr = Expression::Get {
+                        object: Box::new(expr),
+                        name,
+                    };
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn finish_call(&mut self, callee: Expression, paren: Token) -> Result {
+        let mut arguments = Vec::new();
+        if self.reader.peek_type() != Some(TokenType::RightParen) {
+            arguments.push(self.expression()?);
+            while let Some(TokenType::Comma) = self.reader.peek_type() {
+                self.reader.advance();
+                arguments.push(self.expression()?);
+            }
+        }
+
+        let closing = match self.reader.peek_type() {
+            Some(TokenType::RightParen) => self.reader.advance().unwrap(),
+            _ => {
                 return Err(Error::RightParenExpected {
-                    line: reader.line(),
-                });
+                    line: self.reader.line(),
+                    opened_line: paren.line,
+                })
+            }
+        };
+
+        Ok(Expression::Call {
+            callee: Box::new(callee),
+            paren: closing,
+            arguments,
+        })
+    }
+
+    fn primary(&mut self) -> Result {
+        self.count_node()?;
+        match self.reader.peek_type() {
+            Some(TokenType::True)
+            | Some(TokenType::False)
+            | Some(TokenType::Nil)
+            | Some(TokenType::Number)
+            | Some(TokenType::String) => {
+                let token = self.reader.advance().unwrap();
+                // Tokens built by hand may lack the value the scanner would have given them.
+                match token.literal {
+                    Some(value) => Ok(Expression::Literal { value }),
+                    None => Err(Error::LiteralExpected {
+                        line: token.line,
+                        lexeme: token.lexeme,
+                    }),
+                }
+            }
+            Some(TokenType::Identifier) => Ok(Expression::Variable {
+                name: self.reader.advance().unwrap(),
+            }),
+            Some(TokenType::LeftParen) => {
+                let paren = self.reader.advance().unwrap();
+                let expr = self.expression()?;
+                let token_type = self.reader.advance().map(|x| x.t);
+                if token_type != Some(TokenType::RightParen) {
+                    return Err(Error::RightParenExpected {
+                        line: self.reader.line(),
+                        opened_line: paren.line,
+                    });
+                }
+                Ok(Expression::Grouping {
+                    expr: Box::new(expr),
+                })
+            }
+            None => Err(Error::ExpressionExpected {
+                line: self.reader.line(),
+            }),
+            // Stand in for the missing expression and go on with the statement.
+            // Tokens that may close the statement are left for it to consume.
+            Some(t) if self.recover => {
+                let error = match t {
+                    TokenType::Semicolon
+                    | TokenType::RightParen
+                    | TokenType::RightBrace
+                    | TokenType::Comma
+                    | TokenType::Eof => Error::ExpressionExpected {
+                        line: self.reader.line(),
+                    },
+                    _ => {
+                        let token = self.reader.advance().unwrap();
+                        Error::UnexpectedToken {
+                            line: token.line,
+                            lexeme: token.lexeme,
+                        }
+                    }
+                };
+                self.errors.push(error);
+                Ok(Expression::Error {
+                    line: self.reader.line(),
+                })
+            }
+            _ => {
+                let token = self.reader.advance().unwrap();
+                Err(Error::UnexpectedToken {
+                    line: token.line,
+                    lexeme: token.lexeme,
+                })
             }
-            Ok(Expression::Grouping {
-                expr: Box::new(expr),
-            })
         }
-        None => Err(Error::ExpressionExpected {
-            line: reader.line(),
-        }),
-        _ => {
-            let token = reader.advance().unwrap();
-            Err(Error::UnexpectedToken {
-                line: token.line,
-                lexeme: token.lexeme,
-            })
+    }
+
+    fn syncronize(&mut self) {
+        // The error came right after a statement ended, so the next one starts here.
+        if self.reader.previous_type() == Some(TokenType::Semicolon) {
+            return;
+        }
+        loop {
+            match self.reader.peek_type() {
+                Some(TokenType::Semicolon) => {
+                    self.reader.advance();
+                    return;
+                }
+                Some(TokenType::Class)
+                | Some(TokenType::Fun)
+                | Some(TokenType::Var)
+                | Some(TokenType::For)
+                | Some(TokenType::If)
+                | Some(TokenType::While)
+                | Some(TokenType::Print)
+                | Some(TokenType::Return)
+                | None => break,
+                _ => self.reader.advance(),
+            };
         }
     }
 }
 
-fn syncronize(reader: &mut Reader) {
-    loop {
-        match reader.peek_type() {
-            Some(TokenType::Semicolon) => {
-                reader.advance();
-                return;
-            }
-            Some(TokenType::Class)
-            | Some(TokenType::Fun)
-            | Some(TokenType::Var)
-            | Some(TokenType::For)
-            | Some(TokenType::If)
-            | Some(TokenType::While)
-            | Some(TokenType::Print)
-            | Some(TokenType::Return)
-            | None => break,
-            _ => reader.advance(),
-        };
+// `++target` and `--target` only apply to variables and properties.
+fn increment(operator: Token, target: Expression) -> Result {
+    match target {
+        Expression::Variable { .. } | Expression::Get { .. } => Ok(Expression::Increment {
+            operator,
+            target: Box::new(target),
+        }),
+        _ => Err(Error::InvalidAssignmentTarget {
+            line: operator.line,
+        }),
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
-    RightParenExpected { line: usize },
-    UnexpectedToken { line: usize, lexeme: String },
-    ExpressionExpected { line: usize },
+    RightParenExpected {
+        line: usize,
+        opened_line: usize,
+    },
+    UnexpectedToken {
+        line: usize,
+        lexeme: String,
+    },
+    ExpressionExpected {
+        line: usize,
+    },
+    SemicolonExpected {
+        line: usize,
+    },
+    TooManyNodes {
+        line: usize,
+        limit: usize,
+    },
+    TooDeeplyNested {
+        line: usize,
+        limit: usize,
+    },
+    TooManyOperators {
+        line: usize,
+        limit: usize,
+    },
+    VariableNameExpected {
+        line: usize,
+    },
+    InvalidAssignmentTarget {
+        line: usize,
+    },
+    RightBraceExpected {
+        line: usize,
+        after: &'static str,
+        opened_line: usize,
+    },
+    LeftParenExpected {
+        line: usize,
+        keyword: &'static str,
+    },
+    ClassNameExpected {
+        line: usize,
+    },
+    LeftBraceExpected {
+        line: usize,
+        before: &'static str,
+    },
+    PropertyNameExpected {
+        line: usize,
+    },
+    LiteralExpected {
+        line: usize,
+        lexeme: String,
+    },
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
-            Self::RightParenExpected { line } => format_error(line, "expect ')' after expression"),
+            Self::RightParenExpected { line, opened_line } if line == opened_line => {
+                format_error(line, "expect ')' after expression")
+            }
+            Self::RightParenExpected { line, opened_line } => format_error(
+                line,
+                format!(
+                    "expect ')' after expression to close '(' opened on line {}",
+                    opened_line
+                ),
+            ),
             Self::UnexpectedToken { line, ref lexeme } => {
                 format_error(line, format!("unexpected token: {:?}", lexeme))
             }
             Self::ExpressionExpected { line } => format_error(line, "expression expected"),
+            Self::SemicolonExpected { line } => format_error(line, "expect ';' after expression"),
+            Self::TooManyNodes { line, limit } => format_error(
+                line,
+                format!("program too large, the limit is {} nodes", limit),
+            ),
+            Self::TooDeeplyNested { line, limit } => format_error(
+                line,
+                format!("nesting too deep, the limit is {} levels", limit),
+            ),
+            Self::TooManyOperators { line, limit } => format_error(
+                line,
+                format!("statement too long, the limit is {} operators", limit),
+            ),
+            Self::VariableNameExpected { line } => format_error(line, "expect variable name"),
+            Self::InvalidAssignmentTarget { line } => {
+                format_error(line, "invalid assignment target")
+            }
+            Self::RightBraceExpected {
+                line,
+                after,
+                opened_line,
+            } if line == opened_line => format_error(line, format!("expect '}}' after {}", after)),
+            Self::RightBraceExpected {
+                line,
+                after,
+                opened_line,
+            } => format_error(
+                line,
+                format!(
+                    "expect '}}' after {} to close '{{' opened on line {}",
+                    after, opened_line
+                ),
+            ),
+            Self::LeftParenExpected { line, keyword } => {
+                format_error(line, format!("expect '(' after '{}'", keyword))
+            }
+            Self::ClassNameExpected { line } => format_error(line, "expect class name"),
+            Self::LeftBraceExpected { line, before } => {
+                format_error(line, format!("expect '{{' before {}", before))
+            }
+            Self::PropertyNameExpected { line } => {
+                format_error(line, "expect property name after '.'")
+            }
+            Self::LiteralExpected { line, ref lexeme } => {
+                format_error(line, format!("literal token without a value: {:?}", lexeme))
+            }
         };
         write!(f, "{}", msg)
     }
 }
 
 struct Reader {
-    iter: std::vec::IntoIter<Token>,
+    // Remaining tokens in reverse order, so the next token is popped from the end.
+    rest: Vec<Token>,
     current: Option<Token>,
-    last_line: usize,
+    // Type and line of the last token advanced past.
+    previous: Option<(TokenType, usize)>,
 }
 
 impl Reader {
-    fn new(tokens: Vec<Token>) -> Self {
-        let mut iter = tokens.into_iter();
-        let current = iter.next();
-        let last_line = current.as_ref().unwrap().line;
+    fn new(mut tokens: Vec<Token>) -> Self {
+        tokens.reverse();
+        let current = tokens.pop();
         Self {
-            last_line,
-            iter,
+            rest: tokens,
             current,
+            previous: None,
         }
     }
 
@@ -209,19 +1082,48 @@ impl Reader {
         self.current.as_ref().map(|x| x.t)
     }
 
+    fn previous_type(&self) -> Option<TokenType> {
+        self.previous.map(|(t, _)| t)
+    }
+
     fn advance(&mut self) -> Option<Token> {
-        let mut next = self.iter.next();
+        let mut next = self.rest.pop();
 
         if let Some(token) = &self.current {
-            self.last_line = token.line;
+            self.previous = Some((token.t, token.line));
         }
 
         std::mem::swap(&mut self.current, &mut next);
         next
     }
 
+    // Line of the last token advanced past, where a missing token is reported.
+    // Before the first advance it is the line of the current token.
     fn line(&self) -> usize {
-        self.last_line
+        match (self.previous, &self.current) {
+            (Some((_, line)), _) => line,
+            (None, Some(token)) => token.line,
+            (None, None) => 1,
+        }
+    }
+
+    // Split the current `--` token into two `-` tokens.
+    fn split_decrement(&mut self) {
+        if let Some(token) = &mut self.current {
+            token.t = TokenType::Minus;
+            token.lexeme = "-".to_owned();
+            self.rest.push(token.clone());
+        }
+    }
+
+    // Number of tokens left, the current one included.
+    fn remaining(&self) -> usize {
+        self.rest.len() + usize::from(self.current.is_some())
+    }
+
+    fn into_buffer(mut self) -> Vec<Token> {
+        self.rest.clear();
+        self.rest
     }
 }
 
@@ -232,6 +1134,10 @@ mod tests {
         *,
     };
 
+    fn parse(tokens: Vec<Token>) -> Result {
+        Parser::new(tokens).parse_expression()
+    }
+
     #[test]
     fn test_parse_literals_true() {
         let tokens = vec![Token {
@@ -549,7 +1455,13 @@ mod tests {
         ];
 
         let err = parse(tokens).unwrap_err();
-        assert_eq!(Error::RightParenExpected { line: 3 }, err);
+        assert_eq!(
+            Error::RightParenExpected {
+                line: 3,
+                opened_line: 2
+            },
+            err
+        );
     }
 
     #[test]
@@ -657,14 +1569,17 @@ mod tests {
         let mut reader = Reader::new(tokens);
 
         assert_eq!(1, reader.line());
+        assert_eq!(None, reader.previous_type());
         assert_eq!(Some(first.t), reader.peek_type());
         assert_eq!(Some(first), reader.advance());
 
         assert_eq!(1, reader.line());
+        assert_eq!(Some(TokenType::Number), reader.previous_type());
         assert_eq!(Some(second.t), reader.peek_type());
         assert_eq!(Some(second), reader.advance());
 
         assert_eq!(2, reader.line());
+        assert_eq!(Some(TokenType::EqualEqual), reader.previous_type());
         assert_eq!(Some(third.t), reader.peek_type());
         assert_eq!(Some(third), reader.advance());
 
@@ -702,12 +1617,24 @@ mod tests {
             },
             stop_token.clone(),
         ];
-        let mut reader = Reader::new(tokens);
+        let mut parser = Parser::new(tokens);
 
-        let res = parse_with_reader(&mut reader);
+        let res = parser.parse_expression();
 
         assert!(res.is_err());
-        assert_eq!(Some(stop_token), reader.advance());
+        assert_eq!(Some(stop_token), parser.reader.advance());
+    }
+
+    #[test]
+    fn test_syncronize_after_semicolon() {
+        let stop_token = identifier("a", 2);
+        let tokens = vec![token(TokenType::Semicolon, None, 1), stop_token.clone()];
+        let mut parser = Parser::new(tokens);
+        parser.reader.advance();
+
+        parser.syncronize();
+
+        assert_eq!(Some(stop_token), parser.reader.advance());
     }
 
     #[test]
@@ -733,33 +1660,690 @@ mod tests {
             },
             stop_token.clone(),
         ];
-        let mut reader = Reader::new(tokens);
+        let mut parser = Parser::new(tokens);
 
-        let res = parse_with_reader(&mut reader);
+        let res = parser.parse_expression();
 
         assert!(res.is_err());
-        assert_eq!(Some(stop_token), reader.advance());
+        assert_eq!(Some(stop_token), parser.reader.advance());
+    }
+
+    fn token(t: TokenType, literal: Option<TokenLiteral>, line: usize) -> Token {
+        Token {
+            t,
+            lexeme: String::new(),
+            literal,
+            line,
+        }
     }
 
     #[test]
-    fn test_error_format() {
-        assert_eq!(
-            "[line 3] Error: expect ')' after expression",
-            format!("{}", Error::RightParenExpected { line: 3 })
-        );
+    fn test_parse_program() {
+        let tokens = vec![
+            token(TokenType::Print, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
         assert_eq!(
-            "[line 3] Error: unexpected token: \"foo\"",
-            format!(
-                "{}",
-                Error::UnexpectedToken {
-                    line: 3,
-                    lexeme: "foo".to_owned()
-                }
-            )
+            vec!["(print 1)", "(; nil)"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_input_expression() {
+        let tokens = vec![
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let input = Parser::new(tokens).parse_input().unwrap();
+
+        assert!(matches!(
+            input,
+            Input::Expression(Expression::Literal { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_input_program() {
+        let tokens = vec![
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Print, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(2.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let input = Parser::new(tokens).parse_input().unwrap();
+
+        match input {
+            Input::Program(statements) => assert_eq!(2, statements.len()),
+            _ => panic!("program expected"),
+        }
+    }
+
+    fn identifier(name: &str, line: usize) -> Token {
+        Token {
+            t: TokenType::Identifier,
+            lexeme: name.to_owned(),
+            literal: None,
+            line,
+        }
+    }
+
+    #[test]
+    fn test_parse_var_declaration() {
+        let tokens = vec![
+            token(TokenType::Var, None, 1),
+            identifier("foo", 1),
+            token(TokenType::Equal, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Var, None, 2),
+            identifier("bar", 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(var foo 1)", "(var bar)"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_variable_name_expected() {
+        let tokens = vec![
+            token(TokenType::Var, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(Error::VariableNameExpected { line: 1 }, err);
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        let tokens = vec![
+            identifier("foo", 1),
+            token(TokenType::Equal, None, 1),
+            identifier("bar", 1),
+            token(TokenType::Equal, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+        ];
+
+        let tree = parse(tokens).unwrap();
+
+        assert_eq!("(= foo (= bar 1))", format!("{}", tree));
+    }
+
+    #[test]
+    fn test_parse_logical() {
+        let tokens = vec![
+            identifier("a", 1),
+            token(TokenType::Or, None, 1),
+            identifier("b", 1),
+            token(TokenType::And, None, 1),
+            identifier("c", 1),
+            token(TokenType::EqualEqual, None, 1),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 1),
+            token(TokenType::Or, None, 1),
+            token(TokenType::False, Some(TokenLiteral::Boolean(false)), 1),
+        ];
+
+        let tree = parse(tokens).unwrap();
+
+        assert_eq!("(or (or a (and b (== c nil))) false)", format!("{}", tree));
+    }
+
+    #[test]
+    fn test_parse_class_declaration() {
+        let tokens = vec![
+            token(TokenType::Class, None, 1),
+            identifier("Foo", 1),
+            token(TokenType::LeftBrace, None, 1),
+            token(TokenType::RightBrace, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(class Foo)"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_class_body_errors() {
+        let tokens = vec![
+            token(TokenType::Class, None, 1),
+            token(TokenType::LeftBrace, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+        assert_eq!(
+            Error::ClassNameExpected { line: 1 },
+            parse_program(tokens, Limits::default()).unwrap_err()
+        );
+
+        let tokens = vec![
+            token(TokenType::Class, None, 1),
+            identifier("Foo", 1),
+            token(TokenType::Eof, None, 1),
+        ];
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            "[line 1] Error: expect '{' before class body",
+            err.to_string()
+        );
+
+        let tokens = vec![
+            token(TokenType::Class, None, 1),
+            identifier("Foo", 1),
+            token(TokenType::LeftBrace, None, 1),
+            identifier("bar", 2),
+            token(TokenType::Eof, None, 2),
+        ];
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            Error::RightBraceExpected {
+                line: 1,
+                after: "class body",
+                opened_line: 1,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_call_and_properties() {
+        let tokens = vec![
+            identifier("a", 1),
+            token(TokenType::LeftParen, None, 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Dot, None, 1),
+            identifier("b", 1),
+            token(TokenType::LeftParen, None, 1),
+            identifier("c", 1),
+            token(TokenType::Comma, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Dot, None, 1),
+            identifier("d", 1),
+            token(TokenType::Equal, None, 1),
+            identifier("e", 1),
+            token(TokenType::Dot, None, 1),
+            identifier("f", 1),
+        ];
+
+        let tree = parse(tokens).unwrap();
+
+        assert_eq!(
+            "(= (. (call (. (call a) b) c 1) d) (. e f))",
+            format!("{}", tree)
+        );
+    }
+
+    #[test]
+    fn test_property_name_expected() {
+        let tokens = vec![
+            identifier("a", 1),
+            token(TokenType::Dot, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+        ];
+
+        let err = parse(tokens).unwrap_err();
+        assert_eq!(Error::PropertyNameExpected { line: 1 }, err);
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let tokens = vec![
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Equal, None, 2),
+            token(TokenType::Number, Some(TokenLiteral::Number(2.0)), 2),
+        ];
+
+        let err = parse(tokens).unwrap_err();
+        assert_eq!(Error::InvalidAssignmentTarget { line: 2 }, err);
+    }
+
+    #[test]
+    fn test_parse_increment() {
+        let tokens = vec![token(TokenType::PlusPlus, None, 1), identifier("a", 1)];
+        let tree = parse(tokens).unwrap();
+        assert_eq!("(++ a)", format!("{}", tree));
+
+        let tokens = vec![
+            token(TokenType::MinusMinus, None, 1),
+            identifier("a", 1),
+            token(TokenType::Dot, None, 1),
+            identifier("b", 1),
+        ];
+        let tree = parse(tokens).unwrap();
+        assert_eq!("(-- (. a b))", format!("{}", tree));
+
+        let tokens = vec![
+            token(TokenType::PlusPlus, None, 1),
+            identifier("a", 1),
+            token(TokenType::LeftParen, None, 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Dot, None, 1),
+            identifier("b", 1),
+        ];
+        let tree = parse(tokens).unwrap();
+        assert_eq!("(++ (. (call a) b))", format!("{}", tree));
+    }
+
+    #[test]
+    fn test_invalid_increment_target() {
+        let tokens = vec![
+            token(TokenType::PlusPlus, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+        ];
+        let err = parse(tokens).unwrap_err();
+        assert_eq!(Error::InvalidAssignmentTarget { line: 1 }, err);
+    }
+
+    #[test]
+    fn test_parse_block() {
+        let tokens = vec![
+            token(TokenType::LeftBrace, None, 1),
+            token(TokenType::Var, None, 1),
+            identifier("foo", 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::LeftBrace, None, 2),
+            token(TokenType::RightBrace, None, 2),
+            token(TokenType::RightBrace, None, 3),
+            token(TokenType::Eof, None, 3),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(block (var foo) (block))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_while() {
+        let tokens = vec![
+            token(TokenType::While, None, 1),
+            token(TokenType::LeftParen, None, 1),
+            identifier("foo", 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Print, None, 2),
+            identifier("foo", 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(while foo (print foo))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_for() {
+        let tokens = vec![
+            token(TokenType::For, None, 1),
+            token(TokenType::LeftParen, None, 1),
+            token(TokenType::Var, None, 1),
+            identifier("i", 1),
+            token(TokenType::Equal, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(0.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            identifier("i", 1),
+            token(TokenType::Less, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(3.0)), 1),
+            token(TokenType::Semicolon, None, 1),
+            identifier("i", 1),
+            token(TokenType::Equal, None, 1),
+            identifier("i", 1),
+            token(TokenType::Plus, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Print, None, 2),
+            identifier("i", 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(block (var i 0) (while (< i 3) (block (print i) (; (= i (+ i 1))))))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse_for_without_clauses() {
+        let tokens = vec![
+            token(TokenType::For, None, 1),
+            token(TokenType::LeftParen, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::RightParen, None, 1),
+            token(TokenType::Print, None, 1),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let program = parse_program(tokens, Limits::default()).unwrap();
+
+        assert_eq!(
+            vec!["(while true (print nil))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_for_right_paren_expected() {
+        let tokens = vec![
+            token(TokenType::For, None, 1),
+            token(TokenType::LeftParen, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            identifier("i", 2),
+            token(TokenType::Print, None, 2),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            Error::RightParenExpected {
+                line: 2,
+                opened_line: 1
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn test_while_left_paren_expected() {
+        let tokens = vec![
+            token(TokenType::While, None, 1),
+            token(TokenType::True, Some(TokenLiteral::Boolean(true)), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Eof, None, 1),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            Error::LeftParenExpected {
+                line: 1,
+                keyword: "while"
+            },
+            err
+        );
+        assert_eq!("[line 1] Error: expect '(' after 'while'", err.to_string());
+    }
+
+    #[test]
+    fn test_right_brace_expected() {
+        let tokens = vec![
+            token(TokenType::LeftBrace, None, 1),
+            token(TokenType::Print, None, 1),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            Error::RightBraceExpected {
+                line: 1,
+                after: "block",
+                opened_line: 1,
+            },
+            err
+        );
+        assert_eq!("[line 1] Error: expect '}' after block", err.to_string());
+
+        let tokens = vec![
+            token(TokenType::LeftBrace, None, 1),
+            token(TokenType::LeftBrace, None, 2),
+            token(TokenType::RightBrace, None, 2),
+            token(TokenType::Print, None, 3),
+            token(TokenType::Nil, Some(TokenLiteral::Nil), 3),
+            token(TokenType::Semicolon, None, 3),
+            token(TokenType::Eof, None, 4),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(
+            "[line 3] Error: expect '}' after block to close '{' opened on line 1",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_program_recovering() {
+        // print 1 + ;
+        // (2;
+        // var a = 3;
+        let tokens = vec![
+            token(TokenType::Print, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Plus, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::LeftParen, None, 2),
+            token(TokenType::Number, Some(TokenLiteral::Number(2.0)), 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Var, None, 3),
+            identifier("a", 3),
+            token(TokenType::Equal, None, 3),
+            token(TokenType::Number, Some(TokenLiteral::Number(3.0)), 3),
+            token(TokenType::Semicolon, None, 3),
+            token(TokenType::Eof, None, 3),
+        ];
+
+        let (program, errors) = parse_program_recovering(tokens, Limits::default());
+
+        assert_eq!(
+            vec!["(print (+ 1 (error)))", "(; (error))", "(var a 3)"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            program[1],
+            Statement::Expression {
+                expr: Expression::Error { line: 2 }
+            }
+        ));
+        assert_eq!(
+            vec![
+                Error::ExpressionExpected { line: 1 },
+                Error::RightParenExpected {
+                    line: 2,
+                    opened_line: 2
+                },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_parse_program_recovering_missing_target() {
+        // var x = --;
+        // print * = 1;
+        let tokens = vec![
+            token(TokenType::Var, None, 1),
+            identifier("x", 1),
+            token(TokenType::Equal, None, 1),
+            token(TokenType::MinusMinus, None, 1),
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Print, None, 2),
+            Token {
+                lexeme: "*".to_owned(),
+                ..token(TokenType::Star, None, 2)
+            },
+            token(TokenType::Equal, None, 2),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 2),
+            token(TokenType::Semicolon, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let (program, errors) = parse_program_recovering(tokens, Limits::default());
+
+        assert_eq!(
+            vec!["(var x (error))", "(print (error))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Error::ExpressionExpected { line: 1 },
+                Error::UnexpectedToken {
+                    line: 2,
+                    lexeme: "*".to_owned()
+                },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_parse_program_recovering_skips_unexpected_tokens() {
+        let tokens = vec![
+            token(TokenType::Print, None, 1),
+            Token {
+                lexeme: "*".to_owned(),
+                ..token(TokenType::Star, None, 1)
+            },
+            token(TokenType::Semicolon, None, 1),
+            token(TokenType::Class, None, 2),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let (program, errors) = parse_program_recovering(tokens, Limits::default());
+
+        assert_eq!(
+            vec!["(print (error))", "(; (error))"],
+            program.iter().map(|s| s.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                Error::UnexpectedToken {
+                    line: 1,
+                    lexeme: "*".to_owned()
+                },
+                Error::ClassNameExpected { line: 2 },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_program() {
+        let tokens = vec![token(TokenType::Eof, None, 1)];
+        assert!(parse_program(tokens, Limits::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_semicolon_expected() {
+        let tokens = vec![
+            token(TokenType::Print, None, 1),
+            token(TokenType::Number, Some(TokenLiteral::Number(1.0)), 1),
+            token(TokenType::Eof, None, 2),
+        ];
+
+        let err = parse_program(tokens, Limits::default()).unwrap_err();
+        assert_eq!(Error::SemicolonExpected { line: 1 }, err);
+    }
+
+    #[test]
+    fn test_grammar_rules_are_defined() {
+        let names: Vec<&str> = GRAMMAR.iter().map(|rule| rule.name).collect();
+        for rule in GRAMMAR {
+            let production = rule
+                .production
+                .replace(['(', ')', '{', '}', '[', ']', ',', '|'], " ");
+            for symbol in production.split_whitespace() {
+                let is_terminal = symbol.starts_with('"')
+                    || symbol.chars().all(|c| c.is_ascii_uppercase() || c == '_');
+                assert!(
+                    is_terminal || names.contains(&symbol),
+                    "rule {} refers to undefined rule {}",
+                    rule.name,
+                    symbol
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_grammar_ebnf() {
+        let ebnf = grammar_ebnf();
+        assert!(ebnf.starts_with("program     = { declaration } , EOF ;\n"));
+        assert_eq!(GRAMMAR.len(), ebnf.lines().count());
+    }
+
+    #[test]
+    fn test_error_format() {
+        assert_eq!(
+            "[line 3] Error: expect ')' after expression",
+            format!(
+                "{}",
+                Error::RightParenExpected {
+                    line: 3,
+                    opened_line: 3
+                }
+            )
+        );
+        assert_eq!(
+            "[line 3] Error: expect ')' after expression to close '(' opened on line 1",
+            format!(
+                "{}",
+                Error::RightParenExpected {
+                    line: 3,
+                    opened_line: 1
+                }
+            )
+        );
+        assert_eq!(
+            "[line 3] Error: unexpected token: \"foo\"",
+            format!(
+                "{}",
+                Error::UnexpectedToken {
+                    line: 3,
+                    lexeme: "foo".to_owned()
+                }
+            )
         );
         assert_eq!(
             "[line 3] Error: expression expected",
             format!("{}", Error::ExpressionExpected { line: 3 })
         );
+        assert_eq!(
+            "[line 3] Error: expect ';' after expression",
+            format!("{}", Error::SemicolonExpected { line: 3 })
+        );
+        assert_eq!(
+            "[line 3] Error: expect variable name",
+            format!("{}", Error::VariableNameExpected { line: 3 })
+        );
+        assert_eq!(
+            "[line 3] Error: invalid assignment target",
+            format!("{}", Error::InvalidAssignmentTarget { line: 3 })
+        );
     }
 }
@@ -1,29 +1,48 @@
-use std::{collections::HashMap, fmt, str::FromStr};
+use std::{fmt, str::FromStr};
 
 use super::{
+    chars::{is_alpha, is_alpha_numeric, is_digit, is_whitespace},
     error::format_error,
+    keywords,
     token::{Literal, Token, TokenType},
 };
 
+pub const DEFAULT_MAX_TOKENS: usize = 1_000_000;
+
 pub struct Scanner {
-    keywords: HashMap<&'static str, TokenType>,
+    max_tokens: usize,
 }
 
 impl Scanner {
     pub fn new() -> Self {
         Scanner {
-            keywords: keywords(),
+            max_tokens: DEFAULT_MAX_TOKENS,
         }
     }
 
+    // Fail instead of scanning more tokens than the limit, the end of file included.
+    pub fn max_tokens(mut self, limit: usize) -> Self {
+        self.max_tokens = limit;
+        self
+    }
+
     pub fn scan_tokens(&self, source: String) -> Result<Vec<Token>, Error> {
-        let mut reader = Reader::new(source);
         let mut tokens = Vec::new();
+        self.scan_tokens_into(source, &mut tokens)?;
+        Ok(tokens)
+    }
+
+    // Scan the source appending tokens to the buffer.
+    // The buffer is cleared first so its allocation can be reused between runs.
+    pub fn scan_tokens_into(&self, source: String, tokens: &mut Vec<Token>) -> Result<(), Error> {
+        tokens.clear();
+        let mut reader = Reader::new(source);
 
         while !reader.is_at_end() {
             reader.set_start();
             if let Some(token) = self.scan_token(&mut reader)? {
                 tokens.push(token);
+                self.check_token_count(tokens, &reader)?;
             }
         }
         tokens.push(Token {
@@ -33,7 +52,17 @@ impl Scanner {
             line: reader.line(),
         });
 
-        Ok(tokens)
+        self.check_token_count(tokens, &reader)
+    }
+
+    fn check_token_count(&self, tokens: &[Token], reader: &Reader) -> Result<(), Error> {
+        if tokens.len() > self.max_tokens {
+            return Err(Error::TooManyTokens {
+                line: reader.line(),
+                limit: self.max_tokens,
+            });
+        }
+        Ok(())
     }
 
     fn scan_token(&self, reader: &mut Reader) -> Result<Option<Token>, Error> {
@@ -45,8 +74,6 @@ impl Scanner {
             '}' => Ok(Some(Self::token(TokenType::RightBrace, reader))),
             ',' => Ok(Some(Self::token(TokenType::Comma, reader))),
             '.' => Ok(Some(Self::token(TokenType::Dot, reader))),
-            '-' => Ok(Some(Self::token(TokenType::Minus, reader))),
-            '+' => Ok(Some(Self::token(TokenType::Plus, reader))),
             ';' => Ok(Some(Self::token(TokenType::Semicolon, reader))),
             '*' => Ok(Some(Self::token(TokenType::Star, reader))),
             '!' => {
@@ -65,6 +92,22 @@ impl Scanner {
                 };
                 Ok(Some(Self::token(t, reader)))
             }
+            '-' => {
+                let t = if Self::match_char('-', reader) {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                };
+                Ok(Some(Self::token(t, reader)))
+            }
+            '+' => {
+                let t = if Self::match_char('+', reader) {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                };
+                Ok(Some(Self::token(t, reader)))
+            }
             '<' => {
                 let t = if Self::match_char('=', reader) {
                     TokenType::LessEqual
@@ -91,14 +134,14 @@ impl Scanner {
                     Ok(Some(Self::token(TokenType::Slash, reader)))
                 }
             }
-            ' ' | '\r' | '\t' | '\n' => Ok(None),
+            c if is_whitespace(c) => Ok(None),
             '"' => {
                 let token = Self::scan_string(reader)?;
                 Ok(Some(token))
             }
-            c if is_digit(c) => Ok(Some(Self::scan_number(reader))),
+            c if is_digit(c) => Ok(Some(Self::scan_number(reader)?)),
             c if is_alpha(c) => Ok(Some(self.scan_identifier(reader))),
-            _ => Err(Error::UnexpectedCharacterError {
+            _ => Err(Error::UnexpectedCharacter {
                 line: reader.line(),
                 c,
             }),
@@ -134,7 +177,7 @@ impl Scanner {
         }
 
         if reader.is_at_end() {
-            return Err(Error::UnterminatedStringError {
+            return Err(Error::UnterminatedString {
                 line: reader.line(),
             });
         }
@@ -150,7 +193,7 @@ impl Scanner {
         ))
     }
 
-    fn scan_number(reader: &mut Reader) -> Token {
+    fn scan_number(reader: &mut Reader) -> Result<Token, Error> {
         while is_digit(reader.peek()) {
             reader.advance();
         }
@@ -163,8 +206,26 @@ impl Scanner {
             }
         }
 
-        let number = f64::from_str(reader.lexeme().as_ref()).unwrap();
-        Self::literal_token(TokenType::Number, Some(Literal::Number(number)), reader)
+        // Parsing never consults the system locale: the decimal separator is always '.'.
+        let lexeme = reader.lexeme();
+        let number = f64::from_str(lexeme.as_ref()).unwrap();
+        if number.is_infinite() {
+            return Err(Error::NumberOverflow {
+                line: reader.line(),
+                literal: lexeme,
+            });
+        }
+        if number >= MAX_SAFE_INTEGER && !is_exact(&lexeme, number) {
+            return Err(Error::NumberPrecisionLoss {
+                line: reader.line(),
+                literal: lexeme,
+            });
+        }
+        Ok(Self::literal_token(
+            TokenType::Number,
+            Some(Literal::Number(number)),
+            reader,
+        ))
     }
 
     fn scan_identifier(&self, reader: &mut Reader) -> Token {
@@ -173,53 +234,62 @@ impl Scanner {
         }
 
         let lexeme = reader.lexeme();
-        let t = self
-            .keywords
-            .get(lexeme.as_str())
-            .unwrap_or(&TokenType::Identifier);
+        let t = keywords::lookup(&lexeme).unwrap_or(TokenType::Identifier);
         let literal = match t {
-            TokenType::Nil => Literal::Nil,
-            TokenType::True => Literal::Boolean(true),
-            TokenType::False => Literal::Boolean(false),
-            _ => Literal::Identifier(lexeme),
+            TokenType::Nil => Some(Literal::Nil),
+            TokenType::True => Some(Literal::Boolean(true)),
+            TokenType::False => Some(Literal::Boolean(false)),
+            _ => None,
         };
-        Self::literal_token(*t, Some(literal), reader)
+        Self::literal_token(t, literal, reader)
     }
 }
 
-fn is_digit(c: char) -> bool {
-    c.is_ascii_digit()
-}
+// Integers above 2^53 can't all be represented exactly by f64.
+// Literals beyond it are checked for being rounded.
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
 
-fn is_alpha(c: char) -> bool {
-    c.is_ascii_alphabetic() || c == '_'
+// Whether `number`, an integer of at least 2^53, is exactly the value of `lexeme`.
+// Such a number has no fractional part, so any non-zero fractional digit was lost.
+fn is_exact(lexeme: &str, number: f64) -> bool {
+    let (integer, fraction) = lexeme.split_once('.').unwrap_or((lexeme, ""));
+    fraction.chars().all(|c| c == '0') && integer.trim_start_matches('0') == exact_digits(number)
 }
 
-fn is_alpha_numeric(c: char) -> bool {
-    is_digit(c) || is_alpha(c)
-}
+// The exact decimal expansion of an integral `number` of at least 2^53.
+// `f64::to_string` can't be used: it prints the shortest digits that round to `number`.
+fn exact_digits(number: f64) -> String {
+    const BASE: u64 = 1_000_000_000;
+    let bits = number.to_bits();
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
 
-fn keywords() -> HashMap<&'static str, TokenType> {
-    let mut m = HashMap::new();
-
-    m.insert("and", TokenType::And);
-    m.insert("class", TokenType::Class);
-    m.insert("else", TokenType::Else);
-    m.insert("false", TokenType::False);
-    m.insert("for", TokenType::For);
-    m.insert("fun", TokenType::Fun);
-    m.insert("if", TokenType::If);
-    m.insert("nil", TokenType::Nil);
-    m.insert("or", TokenType::Or);
-    m.insert("print", TokenType::Print);
-    m.insert("return", TokenType::Return);
-    m.insert("super", TokenType::Super);
-    m.insert("this", TokenType::This);
-    m.insert("true", TokenType::True);
-    m.insert("var", TokenType::Var);
-    m.insert("while", TokenType::While);
-
-    m
+    // Little-endian limbs of nine decimal digits each.
+    let mut limbs = vec![
+        mantissa % BASE,
+        mantissa / BASE % BASE,
+        mantissa / BASE / BASE,
+    ];
+    for _ in 0..exponent {
+        let mut carry = 0;
+        for limb in limbs.iter_mut() {
+            let doubled = *limb * 2 + carry;
+            *limb = doubled % BASE;
+            carry = doubled / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    while limbs.len() > 1 && limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+
+    let mut digits = limbs.pop().unwrap().to_string();
+    for limb in limbs.iter().rev() {
+        digits.push_str(&format!("{:09}", limb));
+    }
+    digits
 }
 
 struct Reader {
@@ -230,8 +300,19 @@ struct Reader {
 }
 
 impl Reader {
+    // Line endings are normalized to '\n': Windows "\r\n" and lone '\r' both end a line,
+    // so line numbers and multi-line strings don't depend on the editor used.
     fn new(source: String) -> Self {
-        let chars = source.chars().collect();
+        let mut chars = Vec::with_capacity(source.len());
+        let mut source = source.chars().peekable();
+        while let Some(c) = source.next() {
+            if c == '\r' {
+                source.next_if_eq(&'\n');
+                chars.push('\n');
+            } else {
+                chars.push(c);
+            }
+        }
         Self {
             chars,
             start: 0,
@@ -284,17 +365,31 @@ impl Reader {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
-    UnterminatedStringError { line: usize },
-    UnexpectedCharacterError { line: usize, c: char },
+    UnterminatedString { line: usize },
+    UnexpectedCharacter { line: usize, c: char },
+    NumberOverflow { line: usize, literal: String },
+    NumberPrecisionLoss { line: usize, literal: String },
+    TooManyTokens { line: usize, limit: usize },
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
-            Self::UnterminatedStringError { line } => format_error(line, "unterminated string"),
-            Self::UnexpectedCharacterError { line, c } => {
+            Self::UnterminatedString { line } => format_error(line, "unterminated string"),
+            Self::UnexpectedCharacter { line, c } => {
                 format_error(line, format!("unexpected character {:?}", c))
             }
+            Self::NumberOverflow { line, ref literal } => {
+                format_error(line, format!("number literal {} is too large", literal))
+            }
+            Self::NumberPrecisionLoss { line, ref literal } => format_error(
+                line,
+                format!("number literal {} can't be represented exactly", literal),
+            ),
+            Self::TooManyTokens { line, limit } => format_error(
+                line,
+                format!("program too large, the limit is {} tokens", limit),
+            ),
         };
         write!(f, "{}", msg)
     }
@@ -417,6 +512,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_increments() {
+        crate::assert_tokens!(
+            "++a - -b---c",
+            "
+            PlusPlus ++ @1
+            Identifier a @1
+            Minus - @1
+            Minus - @1
+            Identifier b @1
+            MinusMinus -- @1
+            Minus - @1
+            Identifier c @1
+            Eof @1
+            "
+        );
+    }
+
     #[test]
     fn test_comparators() {
         let scanner = Scanner::new();
@@ -616,6 +729,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_number_locale_independent() {
+        let scanner = Scanner::new();
+        let scan = |source: &str| {
+            scanner
+                .scan_tokens(source.to_owned())
+                .unwrap()
+                .into_iter()
+                .map(|token| (token.t, token.literal))
+                .collect::<Vec<_>>()
+        };
+
+        // A comma is never a decimal separator.
+        assert_eq!(
+            vec![
+                (TokenType::Number, Some(Literal::Number(1.0))),
+                (TokenType::Comma, None),
+                (TokenType::Number, Some(Literal::Number(5.0))),
+                (TokenType::Eof, None),
+            ],
+            scan("1,5")
+        );
+        assert_eq!(
+            vec![
+                (TokenType::Number, Some(Literal::Number(1000.5))),
+                (TokenType::Eof, None),
+            ],
+            scan("1000.5")
+        );
+
+        // Only ASCII digits make up numbers.
+        assert_eq!(
+            Err(Error::UnexpectedCharacter { line: 1, c: '٣' }),
+            scanner.scan_tokens("٣".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let scanner = Scanner::new();
+        let lines = |source: &str| {
+            scanner
+                .scan_tokens(source.to_owned())
+                .unwrap()
+                .into_iter()
+                .map(|token| token.line)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![1, 2, 3, 3], lines("a\r\nb\r\nc"));
+        assert_eq!(vec![1, 2, 3, 3], lines("a\rb\rc"));
+        assert_eq!(vec![1, 2, 3, 4, 4], lines("a\r\nb\nc\rd"));
+        assert_eq!(vec![2, 2], lines("// comment\r\nfoo"));
+        assert_eq!(vec![3, 3], lines("\r\n\r\nfoo"));
+    }
+
+    #[test]
+    fn test_string_line_endings() {
+        let scanner = Scanner::new();
+        let tokens = scanner.scan_tokens("\"a\r\nb\rc\"\r\n".to_owned()).unwrap();
+        assert_eq!(
+            Some(Literal::String("a\nb\nc".to_owned())),
+            tokens[0].literal
+        );
+        assert_eq!(3, tokens[0].line);
+        assert_eq!(4, tokens[1].line);
+    }
+
     #[test]
     fn test_identifiers() {
         let scanner = Scanner::new();
@@ -626,13 +807,13 @@ mod tests {
                     t: TokenType::Identifier,
                     line: 1,
                     lexeme: "foo".to_owned(),
-                    literal: Some(Literal::Identifier("foo".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Identifier,
                     line: 1,
                     lexeme: "bar".to_owned(),
-                    literal: Some(Literal::Identifier("bar".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Eof,
@@ -669,79 +850,79 @@ mod tests {
                     t: TokenType::And,
                     line: 1,
                     lexeme: "and".to_owned(),
-                    literal: Some(Literal::Identifier("and".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Class,
                     line: 2,
                     lexeme: "class".to_owned(),
-                    literal: Some(Literal::Identifier("class".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Else,
                     line: 3,
                     lexeme: "else".to_owned(),
-                    literal: Some(Literal::Identifier("else".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::For,
                     line: 4,
                     lexeme: "for".to_owned(),
-                    literal: Some(Literal::Identifier("for".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Fun,
                     line: 5,
                     lexeme: "fun".to_owned(),
-                    literal: Some(Literal::Identifier("fun".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::If,
                     line: 6,
                     lexeme: "if".to_owned(),
-                    literal: Some(Literal::Identifier("if".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Or,
                     line: 7,
                     lexeme: "or".to_owned(),
-                    literal: Some(Literal::Identifier("or".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Print,
                     line: 8,
                     lexeme: "print".to_owned(),
-                    literal: Some(Literal::Identifier("print".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Return,
                     line: 9,
                     lexeme: "return".to_owned(),
-                    literal: Some(Literal::Identifier("return".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Super,
                     line: 10,
                     lexeme: "super".to_owned(),
-                    literal: Some(Literal::Identifier("super".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::This,
                     line: 11,
                     lexeme: "this".to_owned(),
-                    literal: Some(Literal::Identifier("this".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Var,
                     line: 12,
                     lexeme: "var".to_owned(),
-                    literal: Some(Literal::Identifier("var".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::While,
                     line: 13,
                     lexeme: "while".to_owned(),
-                    literal: Some(Literal::Identifier("while".to_owned())),
+                    literal: None,
                 },
                 Token {
                     t: TokenType::Eof,
@@ -798,7 +979,7 @@ mod tests {
         let scanner = Scanner::new();
         let source = "?%".to_owned();
         assert_eq!(
-            Err(Error::UnexpectedCharacterError { line: 1, c: '?' }),
+            Err(Error::UnexpectedCharacter { line: 1, c: '?' }),
             scanner.scan_tokens(source)
         );
     }
@@ -808,20 +989,90 @@ mod tests {
         let scanner = Scanner::new();
         let source = "\"foo".to_owned();
         assert_eq!(
-            Err(Error::UnterminatedStringError { line: 1 }),
+            Err(Error::UnterminatedString { line: 1 }),
             scanner.scan_tokens(source)
         );
     }
 
+    #[test]
+    fn test_number_overflow() {
+        let scanner = Scanner::new();
+        let literal = format!("1{}", "0".repeat(400));
+        assert_eq!(
+            Err(Error::NumberOverflow {
+                line: 1,
+                literal: literal.clone(),
+            }),
+            scanner.scan_tokens(literal)
+        );
+    }
+
+    #[test]
+    fn test_number_precision_loss() {
+        let scanner = Scanner::new();
+        assert_eq!(
+            Err(Error::NumberPrecisionLoss {
+                line: 1,
+                literal: "9007199254740993".to_owned(),
+            }),
+            scanner.scan_tokens("9007199254740993".to_owned())
+        );
+        assert!(scanner.scan_tokens("9007199254740992".to_owned()).is_ok());
+        assert!(scanner
+            .scan_tokens("9007199254740992.000".to_owned())
+            .is_ok());
+        assert!(scanner.scan_tokens("0.5".to_owned()).is_ok());
+
+        // 2^70 is exact even though f64's shortest form is 1180591620717411300000.
+        assert!(scanner
+            .scan_tokens("1180591620717411303424".to_owned())
+            .is_ok());
+
+        // 1e23 rounds to 99999999999999991611392, which prints as 100000000000000000000000.
+        for literal in [
+            "100000000000000000000000",
+            "9007199254740993.0",
+            "9007199254740992.5",
+        ] {
+            assert_eq!(
+                Err(Error::NumberPrecisionLoss {
+                    line: 1,
+                    literal: literal.to_owned(),
+                }),
+                scanner.scan_tokens(literal.to_owned())
+            );
+        }
+    }
+
     #[test]
     fn test_error_format() {
         assert_eq!(
             "[line 3] Error: unterminated string",
-            format!("{}", Error::UnterminatedStringError { line: 3 })
+            format!("{}", Error::UnterminatedString { line: 3 })
         );
         assert_eq!(
             "[line 4] Error: unexpected character '%'",
-            format!("{}", Error::UnexpectedCharacterError { line: 4, c: '%' })
+            format!("{}", Error::UnexpectedCharacter { line: 4, c: '%' })
+        );
+        assert_eq!(
+            "[line 2] Error: number literal 1e400 is too large",
+            format!(
+                "{}",
+                Error::NumberOverflow {
+                    line: 2,
+                    literal: "1e400".to_owned()
+                }
+            )
+        );
+        assert_eq!(
+            "[line 2] Error: number literal 9007199254740993 can't be represented exactly",
+            format!(
+                "{}",
+                Error::NumberPrecisionLoss {
+                    line: 2,
+                    literal: "9007199254740993".to_owned()
+                }
+            )
         );
     }
 }
@@ -0,0 +1,319 @@
+use super::{
+    expression::{self, Expression},
+    token::Token,
+};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum Statement {
+    Block {
+        statements: Vec<Statement>,
+    },
+    Class {
+        name: Token,
+    },
+    Expression {
+        expr: Expression,
+    },
+    Print {
+        expr: Expression,
+    },
+    Var {
+        name: Token,
+        initializer: Option<Expression>,
+    },
+    While {
+        condition: Expression,
+        body: Box<Statement>,
+    },
+}
+
+impl fmt::Display for Statement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Statement::Block { statements } => {
+                write!(f, "(block")?;
+                for stmt in statements {
+                    write!(f, " {}", stmt)?;
+                }
+                write!(f, ")")
+            }
+            Statement::Class { name } => write!(f, "(class {})", name.lexeme),
+            Statement::Expression { expr } => write!(f, "(; {})", expr),
+            Statement::Print { expr } => write!(f, "(print {})", expr),
+            Statement::Var {
+                name,
+                initializer: Some(initializer),
+            } => write!(f, "(var {} {})", name.lexeme, initializer),
+            Statement::Var {
+                name,
+                initializer: None,
+            } => write!(f, "(var {})", name.lexeme),
+            Statement::While { condition, body } => write!(f, "(while {} {})", condition, body),
+        }
+    }
+}
+
+pub fn walk_stmt<V: Visitor>(stmt: &Statement, v: &V) -> V::Result {
+    match stmt {
+        Statement::Block { statements } => v.visit_block_stmt(statements),
+        Statement::Class { name } => v.visit_class_stmt(name),
+        Statement::Expression { expr } => v.visit_expression_stmt(expr),
+        Statement::Print { expr } => v.visit_print_stmt(expr),
+        Statement::Var { name, initializer } => v.visit_var_stmt(name, initializer.as_ref()),
+        Statement::While { condition, body } => v.visit_while_stmt(condition, body),
+    }
+}
+
+pub trait Visitor {
+    type Result;
+
+    fn visit_block_stmt(&self, statements: &[Statement]) -> Self::Result;
+    fn visit_class_stmt(&self, name: &Token) -> Self::Result;
+    fn visit_expression_stmt(&self, expr: &Expression) -> Self::Result;
+    fn visit_print_stmt(&self, expr: &Expression) -> Self::Result;
+    fn visit_var_stmt(&self, name: &Token, initializer: Option<&Expression>) -> Self::Result;
+    fn visit_while_stmt(&self, condition: &Expression, body: &Statement) -> Self::Result;
+}
+
+pub fn pretty_print(statements: &[Statement]) -> String {
+    print_lines(statements, &AstPrinter {})
+}
+
+pub fn rpn_print(statements: &[Statement]) -> String {
+    print_lines(statements, &RpnPrinter {})
+}
+
+fn print_lines<V: Visitor<Result = String>>(statements: &[Statement], v: &V) -> String {
+    statements
+        .iter()
+        .map(|stmt| walk_stmt(stmt, v))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+struct AstPrinter;
+
+impl Visitor for AstPrinter {
+    type Result = String;
+
+    fn visit_block_stmt(&self, statements: &[Statement]) -> Self::Result {
+        let mut result = "(block".to_owned();
+        for stmt in statements {
+            result.push(' ');
+            result.push_str(&walk_stmt(stmt, self));
+        }
+        result.push(')');
+        result
+    }
+
+    fn visit_class_stmt(&self, name: &Token) -> Self::Result {
+        format!("(class {})", name.lexeme)
+    }
+
+    fn visit_expression_stmt(&self, expr: &Expression) -> Self::Result {
+        format!("(; {})", expression::pretty_print(expr))
+    }
+
+    fn visit_print_stmt(&self, expr: &Expression) -> Self::Result {
+        format!("(print {})", expression::pretty_print(expr))
+    }
+
+    fn visit_var_stmt(&self, name: &Token, initializer: Option<&Expression>) -> Self::Result {
+        match initializer {
+            Some(initializer) => format!(
+                "(var {} {})",
+                name.lexeme,
+                expression::pretty_print(initializer)
+            ),
+            None => format!("(var {})", name.lexeme),
+        }
+    }
+
+    fn visit_while_stmt(&self, condition: &Expression, body: &Statement) -> Self::Result {
+        format!(
+            "(while {} {})",
+            expression::pretty_print(condition),
+            walk_stmt(body, self)
+        )
+    }
+}
+
+// Prints statements in reverse Polish notation: the expression first, then the statement keyword.
+struct RpnPrinter;
+
+impl Visitor for RpnPrinter {
+    type Result = String;
+
+    fn visit_block_stmt(&self, statements: &[Statement]) -> Self::Result {
+        let mut result = String::new();
+        for stmt in statements {
+            result.push_str(&walk_stmt(stmt, self));
+            result.push(' ');
+        }
+        result.push_str("block");
+        result
+    }
+
+    fn visit_class_stmt(&self, name: &Token) -> Self::Result {
+        format!("{} class", name.lexeme)
+    }
+
+    fn visit_expression_stmt(&self, expr: &Expression) -> Self::Result {
+        format!("{} ;", expression::rpn_print(expr))
+    }
+
+    fn visit_print_stmt(&self, expr: &Expression) -> Self::Result {
+        format!("{} print", expression::rpn_print(expr))
+    }
+
+    fn visit_var_stmt(&self, name: &Token, initializer: Option<&Expression>) -> Self::Result {
+        match initializer {
+            Some(initializer) => {
+                format!("{} {} var", name.lexeme, expression::rpn_print(initializer))
+            }
+            None => format!("{} var", name.lexeme),
+        }
+    }
+
+    fn visit_while_stmt(&self, condition: &Expression, body: &Statement) -> Self::Result {
+        format!(
+            "{} {} while",
+            expression::rpn_print(condition),
+            walk_stmt(body, self)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::token::{Literal as TokenLiteral, Token, TokenType};
+    use super::*;
+
+    fn program() -> Vec<Statement> {
+        vec![
+            Statement::Print {
+                expr: Expression::Unary {
+                    operator: Token {
+                        t: TokenType::Minus,
+                        lexeme: "-".to_owned(),
+                        literal: None,
+                        line: 1,
+                    },
+                    right: Box::new(Expression::Literal {
+                        value: TokenLiteral::Number(2.0),
+                    }),
+                },
+            },
+            Statement::Expression {
+                expr: Expression::Literal {
+                    value: TokenLiteral::Nil,
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        assert_eq!("(print (- 2))\n(; nil)", pretty_print(&program()));
+    }
+
+    #[test]
+    fn test_rpn_print() {
+        assert_eq!("2 - print\nnil ;", rpn_print(&program()));
+    }
+
+    #[test]
+    fn test_print_class_stmt() {
+        let statements = vec![Statement::Class {
+            name: Token {
+                t: TokenType::Identifier,
+                lexeme: "Foo".to_owned(),
+                literal: None,
+                line: 1,
+            },
+        }];
+        assert_eq!("(class Foo)", pretty_print(&statements));
+        assert_eq!("Foo class", rpn_print(&statements));
+        assert_eq!("(class Foo)", statements[0].to_string());
+    }
+
+    #[test]
+    fn test_print_var_stmt() {
+        let name = Token {
+            t: TokenType::Identifier,
+            lexeme: "foo".to_owned(),
+            literal: None,
+            line: 1,
+        };
+        let statements = vec![
+            Statement::Var {
+                name: name.clone(),
+                initializer: Some(Expression::Literal {
+                    value: TokenLiteral::Number(1.0),
+                }),
+            },
+            Statement::Var {
+                name,
+                initializer: None,
+            },
+        ];
+        assert_eq!("(var foo 1)\n(var foo)", pretty_print(&statements));
+        assert_eq!("foo 1 var\nfoo var", rpn_print(&statements));
+        assert_eq!("(var foo 1)", statements[0].to_string());
+    }
+
+    #[test]
+    fn test_print_block_stmt() {
+        let statements = vec![Statement::Block {
+            statements: program(),
+        }];
+        assert_eq!("(block (print (- 2)) (; nil))", pretty_print(&statements));
+        assert_eq!("2 - print nil ; block", rpn_print(&statements));
+        assert_eq!("(block (print (- 2)) (; nil))", statements[0].to_string());
+
+        let empty = Statement::Block { statements: vec![] };
+        assert_eq!("(block)", empty.to_string());
+    }
+
+    #[test]
+    fn test_print_while_stmt() {
+        let statements = vec![Statement::While {
+            condition: Expression::Literal {
+                value: TokenLiteral::Boolean(false),
+            },
+            body: Box::new(Statement::Block {
+                statements: program(),
+            }),
+        }];
+        assert_eq!(
+            "(while false (block (print (- 2)) (; nil)))",
+            pretty_print(&statements)
+        );
+        assert_eq!("false 2 - print nil ; block while", rpn_print(&statements));
+        assert_eq!(
+            "(while false (block (print (- 2)) (; nil)))",
+            statements[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_expression_stmt() {
+        let stmt = Statement::Expression {
+            expr: Expression::Literal {
+                value: TokenLiteral::Number(2.0),
+            },
+        };
+        assert_eq!("(; 2)", format!("{}", stmt));
+    }
+
+    #[test]
+    fn test_format_print_stmt() {
+        let stmt = Statement::Print {
+            expr: Expression::Literal {
+                value: TokenLiteral::String("foo".to_owned()),
+            },
+        };
+        assert_eq!("(print \"foo\")", format!("{}", stmt));
+    }
+}
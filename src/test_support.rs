@@ -0,0 +1,64 @@
+use super::{
+    scanner::Scanner,
+    token::{self, Token},
+};
+
+// Render the tokens as a compact snapshot, one token per line.
+pub fn snapshot(tokens: &[Token]) -> String {
+    token::dump(tokens)
+}
+
+// Scan the source and render the token stream as a snapshot.
+// A scan error is rendered as its message.
+pub fn snapshot_tokens(source: &str) -> Result<String, String> {
+    Scanner::new()
+        .scan_tokens(source.to_owned())
+        .map(|tokens| snapshot(&tokens))
+        .map_err(|e| e.to_string())
+}
+
+// Assert the source scans into the expected token snapshot.
+// Lines of the expected snapshot are trimmed so it can be indented freely.
+#[macro_export]
+macro_rules! assert_tokens {
+    ($source:expr, $expected:expr) => {{
+        let expected = $expected
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<::std::vec::Vec<_>>()
+            .join("\n");
+        assert_eq!(Ok(expected), $crate::test_support::snapshot_tokens($source));
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_tokens() {
+        crate::assert_tokens!(
+            "var foo = 12.5;\nprint \"bar\";",
+            "
+            Var var @1
+            Identifier foo @1
+            Equal = @1
+            Number 12.5 @1
+            Semicolon ; @1
+            Print print @2
+            String \"bar\" @2
+            Semicolon ; @2
+            Eof @2
+            "
+        );
+    }
+
+    #[test]
+    fn test_snapshot_tokens_error() {
+        assert_eq!(
+            Err("[line 1] Error: unterminated string".to_owned()),
+            snapshot_tokens("\"foo")
+        );
+    }
+}
@@ -16,6 +16,8 @@ pub enum TokenType {
     Star,
 
     // One or two character tokens.
+    MinusMinus,
+    PlusPlus,
     Bang,
     BangEqual,
     Equal,
@@ -66,6 +68,8 @@ impl fmt::Display for TokenType {
             TokenType::Slash => write!(f, "/"),
             TokenType::Star => write!(f, "*"),
 
+            TokenType::MinusMinus => write!(f, "--"),
+            TokenType::PlusPlus => write!(f, "++"),
             TokenType::Bang => write!(f, "!"),
             TokenType::BangEqual => write!(f, "!="),
             TokenType::Equal => write!(f, "="),
@@ -127,7 +131,6 @@ pub enum Literal {
     Boolean(bool),
     Number(f64),
     String(String),
-    Identifier(String),
 }
 
 impl fmt::Display for Literal {
@@ -137,15 +140,86 @@ impl fmt::Display for Literal {
             Literal::Boolean(b) => write!(f, "{}", b),
             Literal::Number(num) => write!(f, "{}", num),
             Literal::String(ref s) => write!(f, "{:?}", s),
-            Literal::Identifier(ref s) => write!(f, "{}", s),
         }
     }
 }
 
+// Render the tokens one per line: the token type, the lexeme (if any)
+// and the line it was found on.
+pub fn dump(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| {
+            if token.lexeme.is_empty() {
+                format!("{:?} @{}", token.t, token.line)
+            } else {
+                format!("{:?} {} @{}", token.t, token.lexeme, token.line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Every token type in declaration order, for tests that go over all of them.
+#[cfg(test)]
+pub const TOKEN_TYPES: [TokenType; 41] = [
+    TokenType::LeftParen,
+    TokenType::RightParen,
+    TokenType::LeftBrace,
+    TokenType::RightBrace,
+    TokenType::Comma,
+    TokenType::Dot,
+    TokenType::Minus,
+    TokenType::Plus,
+    TokenType::Semicolon,
+    TokenType::Slash,
+    TokenType::Star,
+    TokenType::MinusMinus,
+    TokenType::PlusPlus,
+    TokenType::Bang,
+    TokenType::BangEqual,
+    TokenType::Equal,
+    TokenType::EqualEqual,
+    TokenType::Greater,
+    TokenType::GreaterEqual,
+    TokenType::Less,
+    TokenType::LessEqual,
+    TokenType::Identifier,
+    TokenType::String,
+    TokenType::Number,
+    TokenType::And,
+    TokenType::Class,
+    TokenType::Else,
+    TokenType::False,
+    TokenType::Fun,
+    TokenType::For,
+    TokenType::If,
+    TokenType::Nil,
+    TokenType::Or,
+    TokenType::Print,
+    TokenType::Return,
+    TokenType::Super,
+    TokenType::This,
+    TokenType::True,
+    TokenType::Var,
+    TokenType::While,
+    TokenType::Eof,
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn token_types_complete() {
+        // Each type is at the index of its discriminant and the last one is Eof,
+        // so the list has no duplicates and no gaps.
+        for (i, t) in TOKEN_TYPES.into_iter().enumerate() {
+            assert_eq!(i, t as usize, "{:?}", t);
+        }
+        assert_eq!(TokenType::Eof as usize + 1, TOKEN_TYPES.len());
+    }
+
     #[test]
     fn literal_display() {
         assert_eq!("nil", format!("{}", Literal::Nil));
@@ -154,7 +228,6 @@ mod tests {
         assert_eq!("2", format!("{}", Literal::Number(2.0)));
         assert_eq!("2.4", format!("{}", Literal::Number(2.4)));
         assert_eq!("\"foo\"", format!("{}", Literal::String("foo".to_owned())));
-        assert_eq!("foo", format!("{}", Literal::Identifier("foo".to_owned())));
     }
 
     #[test]
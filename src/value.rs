@@ -1,25 +1,116 @@
-use std::fmt;
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
 
-#[derive(PartialEq, Debug)]
+#[derive(Debug, Clone)]
 pub enum Value {
     Nil,
     Boolean(bool),
     Number(f64),
     String(String),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
 }
 
+// Classes and instances are compared by identity, like in Lox.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    fields: HashMap<String, Value>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn set(&mut self, name: String, value: Value) {
+        self.fields.insert(name, value);
+    }
+}
+
+// Dropping a long linked list of instances would recurse once per node and overflow the stack.
+// Instead the fields are drained into a worklist, and the instances only owned by it are
+// taken apart in a loop.
+impl Drop for Instance {
+    fn drop(&mut self) {
+        let mut pending: Vec<Value> = self.fields.drain().map(|(_, value)| value).collect();
+        while let Some(value) = pending.pop() {
+            if let Value::Instance(instance) = value {
+                if let Ok(instance) = Rc::try_unwrap(instance) {
+                    let mut instance = instance.into_inner();
+                    pending.extend(instance.fields.drain().map(|(_, value)| value));
+                }
+            }
+        }
+    }
+}
+
+// Fields are left out: an instance can refer to itself through them.
+impl fmt::Debug for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Instance")
+            .field("class", &self.class.name)
+            .finish_non_exhaustive()
+    }
+}
+
+// How print shows the value: strings are written as is.
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Value::Nil => write!(f, "nil"),
             Value::Boolean(b) => write!(f, "{}", b),
+            // Formatting ignores the system locale: no digit grouping, '.' as decimal separator.
             Value::Number(num) => write!(f, "{}", num),
-            Value::String(ref s) => write!(f, "{:?}", s),
+            Value::String(ref s) => write!(f, "{}", s),
+            Value::Class(ref class) => write!(f, "{}", class.name),
+            Value::Instance(ref instance) => write!(f, "{} instance", instance.borrow().class.name),
+        }
+    }
+}
+
+// How the REPL echoes the value: strings are quoted so they stand apart from
+// the other values, as in `"nil"` and `nil`.
+pub struct Repr<'a>(&'a Value);
+
+impl fmt::Display for Repr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Value::String(s) => write!(f, "{:?}", s),
+            value => write!(f, "{}", value),
         }
     }
 }
 
 impl Value {
+    pub fn repr(&self) -> Repr<'_> {
+        Repr(self)
+    }
+
     pub fn is_nil(&self) -> bool {
         matches!(self, Value::Nil)
     }
@@ -39,21 +130,84 @@ impl Value {
     pub fn unwrap_boolean(&self) -> bool {
         match self {
             Value::Boolean(b) => *b,
-            _ => panic!("unwrapping a value failed: value is {}", self),
+            _ => panic!("unwrapping a value failed: value is {}", self.repr()),
         }
     }
 
     pub fn unwrap_number(&self) -> f64 {
         match self {
             Value::Number(num) => *num,
-            _ => panic!("unwrapping a value failed: value is {}", self),
+            _ => panic!("unwrapping a value failed: value is {}", self.repr()),
         }
     }
 
     pub fn unwrap_string(&self) -> &str {
         match self {
             Value::String(s) => s,
-            _ => panic!("unwrapping a value failed: value is {}", self),
+            _ => panic!("unwrapping a value failed: value is {}", self.repr()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_class_and_instance() {
+        let class = Rc::new(Class {
+            name: "Foo".to_owned(),
+        });
+        let instance = Rc::new(RefCell::new(Instance::new(class.clone())));
+        instance
+            .borrow_mut()
+            .set("me".to_owned(), Value::Instance(instance.clone()));
+
+        assert_eq!("Foo", Value::Class(class.clone()).to_string());
+        assert_eq!(
+            "Foo instance",
+            Value::Instance(instance.clone()).to_string()
+        );
+        assert_eq!(
+            Some(Value::Instance(instance.clone())),
+            instance.borrow().get("me")
+        );
+        assert_eq!(None, instance.borrow().get("other"));
+
+        // identity, not structure
+        let other = Rc::new(RefCell::new(Instance::new(class.clone())));
+        assert_ne!(Value::Instance(instance.clone()), Value::Instance(other));
+        let copy = Rc::new(Class {
+            name: "Foo".to_owned(),
+        });
+        assert_ne!(Value::Class(class), Value::Class(copy));
+
+        // break the cycle
+        instance.borrow_mut().set("me".to_owned(), Value::Nil);
+    }
+
+    #[test]
+    fn test_display_and_repr() {
+        let string = Value::String("a \"b\"".to_owned());
+        assert_eq!("a \"b\"", string.to_string());
+        assert_eq!("\"a \\\"b\\\"\"", string.repr().to_string());
+
+        assert_eq!("nil", Value::Nil.repr().to_string());
+        assert_eq!("2.5", Value::Number(2.5).repr().to_string());
+        assert_eq!("true", Value::Boolean(true).repr().to_string());
+    }
+
+    #[test]
+    fn test_format_number() {
+        let numbers = [
+            (3.0, "3"),
+            (-0.5, "-0.5"),
+            (1234567.25, "1234567.25"),
+            (1e21, "1000000000000000000000"),
+            (0.1 + 0.2, "0.30000000000000004"),
+        ];
+        for (number, expected) in numbers {
+            assert_eq!(expected, Value::Number(number).to_string());
         }
     }
 }
@@ -0,0 +1,348 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+fn fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests/fixtures");
+    path.push(name);
+    path.to_str().unwrap().to_owned()
+}
+
+fn lox(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_relox"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn lox_with_stdin(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_relox"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn test_no_command_is_usage_error() {
+    let output = lox(&[]);
+    assert_eq!(Some(64), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).starts_with("Usage:"));
+}
+
+#[test]
+fn test_unknown_command_is_usage_error() {
+    let output = lox(&["foo"]);
+    assert_eq!(Some(64), output.status.code());
+    assert_eq!("", stdout(&output));
+}
+
+#[test]
+fn test_run_file() {
+    let output = lox(&["run", &fixture("expression.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("3\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn test_run_prints_while_running() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_relox"))
+        .args(["run", &fixture("slow.lox")])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The lines are read on another thread, so a missing line fails the test instead of hanging it.
+    let reader = BufReader::new(child.stdout.take().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let timeout = Duration::from_secs(10);
+    let first = receiver.recv_timeout(timeout);
+    let second = receiver.recv_timeout(timeout);
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(Ok("0".to_owned()), first);
+    assert_eq!(Ok("1".to_owned()), second);
+}
+
+#[test]
+fn test_run_output_closed() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_relox"))
+        .args(["run", &fixture("count.lox")])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    assert_eq!("0", lines.next().unwrap().unwrap());
+    // The output is much larger than the pipe buffer, so the program is still writing.
+    drop(lines);
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(Some(74), output.status.code());
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn test_run_scan_error() {
+    let output = lox(&["run", &fixture("scan-error.lox")]);
+    assert_eq!(Some(65), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert_eq!(
+        "[line 1] Error: unexpected character '?'\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_run_parse_error() {
+    let output = lox(&["run", &fixture("parse-error.lox")]);
+    assert_eq!(Some(65), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert_eq!(
+        "[line 1] Error: expect ')' after expression\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_run_runtime_error() {
+    let output = lox(&["run", &fixture("runtime-error.lox")]);
+    assert_eq!(Some(70), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert_eq!(
+        "[line 1] Error: operands must be numbers\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_run_missing_file() {
+    let output = lox(&["run", &fixture("missing.lox")]);
+    assert_eq!(Some(66), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).starts_with("can't read"));
+}
+
+#[test]
+fn test_run_unknown_flag_is_usage_error() {
+    let output = lox(&["run", "--foo", &fixture("expression.lox")]);
+    assert_eq!(Some(64), output.status.code());
+    assert_eq!("", stdout(&output));
+}
+
+#[test]
+fn test_prompt() {
+    let output = lox_with_stdin(&["run"], "1 + 2\n\"a\" - 1\nprint 3;\n");
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("> 3\n> > 3\n> ", stdout(&output));
+    assert_eq!(
+        "[line 1] Error: operands must be numbers\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_ast() {
+    let output = lox(&["ast", &fixture("expression.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("(print (+ 1 2))\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+
+    let output = lox(&["ast", "--format=rpn", &fixture("expression.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("1 2 + print\n", stdout(&output));
+}
+
+#[test]
+fn test_ast_error() {
+    let output = lox(&["ast", &fixture("parse-error.lox")]);
+    assert_eq!(Some(65), output.status.code());
+    assert_eq!("(; (error))\n", stdout(&output));
+    assert_eq!(
+        "[line 1] Error: expect ')' after expression\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_ast_usage_errors() {
+    assert_eq!(Some(64), lox(&["ast"]).status.code());
+    let output = lox(&["ast", "--format=foo", &fixture("expression.lox")]);
+    assert_eq!(Some(64), output.status.code());
+}
+
+#[test]
+fn test_unknown_flag_is_usage_error() {
+    for command in ["run", "ast", "tokenize", "highlight", "diff-ast"] {
+        let output = lox(&[command, "--foo", &fixture("expression.lox")]);
+        assert_eq!(Some(64), output.status.code(), "{}", command);
+        assert!(stderr(&output).starts_with("Usage:"), "{}", command);
+    }
+    assert_eq!(Some(64), lox(&["examples", "--foo"]).status.code());
+}
+
+#[test]
+fn test_encoding_flag() {
+    let latin1 = fixture("latin1.lox");
+    let output = lox(&["highlight", "--format=ansi", "--encoding=latin1", &latin1]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).contains("\"café\""));
+
+    let output = lox(&["highlight", &latin1]);
+    assert_eq!(Some(65), output.status.code());
+
+    let output = lox(&["diff-ast", "--encoding=latin1", &latin1, &latin1]);
+    assert_eq!(Some(0), output.status.code());
+
+    let output = lox(&["diff-ast", &latin1, &latin1]);
+    assert_eq!(Some(65), output.status.code());
+}
+
+#[test]
+fn test_diff_ast() {
+    let output = lox(&[
+        "diff-ast",
+        &fixture("expression.lox"),
+        &fixture("expression.lox"),
+    ]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("", stdout(&output));
+
+    let output = lox(&[
+        "diff-ast",
+        &fixture("expression.lox"),
+        &fixture("runtime-error.lox"),
+    ]);
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "at statement[0]:\n- (print (+ 1 2))\n+ (; (- \"a\" 1))\n",
+        stdout(&output)
+    );
+
+    let output = lox(&[
+        "diff-ast",
+        &fixture("expression.lox"),
+        &fixture("parse-error.lox"),
+    ]);
+    assert_eq!(Some(65), output.status.code());
+    assert_eq!(Some(64), lox(&["diff-ast"]).status.code());
+    let output = lox(&["diff-ast", &fixture("expression.lox")]);
+    assert_eq!(Some(64), output.status.code());
+}
+
+#[test]
+fn test_grammar() {
+    let output = lox(&["grammar"]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).starts_with("program     = { declaration } , EOF ;\n"));
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn test_run_file_with_bom() {
+    let output = lox(&["run", &fixture("bom.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("bom\n", stdout(&output));
+}
+
+#[test]
+fn test_run_invalid_utf8() {
+    let output = lox(&["run", &fixture("latin1.lox")]);
+    assert_eq!(Some(65), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).ends_with("latin1.lox: invalid UTF-8 at byte 10\n"));
+}
+
+#[test]
+fn test_run_latin1() {
+    let output = lox(&["run", "--encoding=latin1", &fixture("latin1.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("café\n", stdout(&output));
+
+    let output = lox(&["run", "--encoding=ebcdic", &fixture("latin1.lox")]);
+    assert_eq!(Some(64), output.status.code());
+}
+
+#[test]
+fn test_run_crlf_file() {
+    let output = lox(&["run", &fixture("crlf.lox")]);
+    assert_eq!(Some(70), output.status.code());
+    assert_eq!("1\n", stdout(&output));
+    assert_eq!(
+        "[line 4] Error: operand must be a number\n",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_highlight() {
+    let output = lox(&["highlight", &fixture("expression.lox"), "--format=ansi"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!(
+        "\x1b[35mprint\x1b[0m \x1b[36m1\x1b[0m + \x1b[36m2\x1b[0m;\n",
+        stdout(&output)
+    );
+
+    let output = lox(&["highlight", &fixture("expression.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).starts_with("<pre class=\"lox\"><code><span class=\"keyword\">"));
+
+    let output = lox(&["highlight", &fixture("scan-error.lox")]);
+    assert_eq!(Some(65), output.status.code());
+}
+
+#[test]
+fn test_examples() {
+    let output = lox(&["examples"]);
+    assert_eq!(Some(0), output.status.code());
+    assert!(stdout(&output).lines().any(|name| name == "hello-world"));
+
+    let output = lox(&["examples", "hello-world"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("Hello, world!\n", stdout(&output));
+
+    let output = lox(&["examples", "missing"]);
+    assert_eq!(Some(66), output.status.code());
+    assert_eq!("unknown example missing\n", stderr(&output));
+}
+
+#[test]
+fn test_run_summary() {
+    let output = lox(&["run", "--summary", &fixture("expression.lox")]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("3\n", stdout(&output));
+    assert_eq!(
+        "statements executed: 1 (syntax tree nodes, after desugaring)\npeak environment depth: 1\n",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,42 @@
+use relox::{Difference, Error, Lox, RuntimeError, Statistics, Warning};
+
+#[test]
+fn test_strict_equality_error() {
+    let mut lox = Lox::builder().strict_equality(true).build();
+    let mut output = String::new();
+    let result = lox.run("print 1 == \"1\";".to_owned(), &mut output);
+    assert!(matches!(
+        result,
+        Err(Error::Runtime(
+            RuntimeError::OperandsMustHaveSameType { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_warnings_and_statistics() {
+    let mut lox = Lox::builder().warn_float_equality(true).build();
+    let mut output = String::new();
+    lox.run("print 0.1 == 0.3;".to_owned(), &mut output)
+        .unwrap();
+    let warnings: Vec<Warning> = lox.take_warnings();
+    assert!(matches!(warnings[..], [Warning::FloatEquality { .. }]));
+    assert_eq!(
+        Statistics {
+            statements: 1,
+            peak_depth: 1,
+        },
+        lox.statistics()
+    );
+}
+
+#[test]
+fn test_diff_ast() {
+    let lox = Lox::new();
+    let differences: Vec<Difference> = lox
+        .diff_ast("print 1;".to_owned(), "print 2;".to_owned())
+        .unwrap();
+    assert_eq!(1, differences.len());
+    assert_eq!("1", differences[0].left);
+    assert_eq!("2", differences[0].right);
+}
@@ -0,0 +1,65 @@
+use relox::{
+    parse_program, parse_tokens, Expression, Input, Limits, ParseError, Parser, Statement, Token,
+    TokenType,
+};
+
+fn token(t: TokenType, lexeme: &str) -> Token {
+    Token {
+        t,
+        lexeme: lexeme.to_owned(),
+        literal: None,
+        line: 1,
+    }
+}
+
+#[test]
+fn test_parse_program() {
+    let tokens = vec![
+        token(TokenType::Print, "print"),
+        token(TokenType::Identifier, "a"),
+        token(TokenType::Semicolon, ";"),
+        token(TokenType::Eof, ""),
+    ];
+    let statements = parse_program(tokens, Limits::default()).unwrap();
+    assert!(matches!(statements[..], [Statement::Print { .. }]));
+}
+
+#[test]
+fn test_parse_tokens_expression() {
+    let mut tokens = vec![token(TokenType::Identifier, "a"), token(TokenType::Eof, "")];
+    let input = parse_tokens(&mut tokens, Limits::default()).unwrap();
+    assert!(matches!(
+        input,
+        Input::Expression(Expression::Variable { .. })
+    ));
+    assert!(tokens.is_empty());
+}
+
+#[test]
+fn test_parser_limits() {
+    let tokens = vec![
+        token(TokenType::Identifier, "a"),
+        token(TokenType::Plus, "+"),
+        token(TokenType::Identifier, "b"),
+        token(TokenType::Eof, ""),
+    ];
+    let limits = Limits {
+        max_nodes: 1,
+        ..Limits::default()
+    };
+    let result = Parser::new(tokens).limits(limits).parse_expression();
+    assert!(matches!(result, Err(ParseError::TooManyNodes { .. })));
+}
+
+#[test]
+fn test_literal_without_value() {
+    let tokens = vec![token(TokenType::Number, "1"), token(TokenType::Eof, "")];
+    let result = Parser::new(tokens).parse_expression();
+    assert_eq!(
+        result.unwrap_err(),
+        ParseError::LiteralExpected {
+            line: 1,
+            lexeme: "1".to_owned(),
+        }
+    );
+}
@@ -0,0 +1,117 @@
+// Golden tests for the command line output.
+// Each case runs the binary and compares its exit code, stdout and stderr with the
+// snapshot in tests/snapshots. After an intended change of the output format, run
+// with UPDATE_SNAPSHOTS=1 to rewrite the snapshots and review their diff.
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+fn assert_snapshot(name: &str, args: &[&str], stdin: Option<&str>) {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_relox"))
+        .current_dir(&root)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.unwrap_or_default().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let mut actual = format!("$ lox {}\n", args.join(" "));
+    if let Some(stdin) = stdin {
+        actual.push_str(&format!("--- stdin\n{}", stdin));
+    }
+    actual.push_str(&format!(
+        "--- status {}\n--- stdout\n{}\n--- stderr\n{}",
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap()
+    ));
+
+    let path = root.join("tests/snapshots").join(format!("{}.snap", name));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("can't read {}: {}", path.display(), e));
+    assert!(
+        expected == actual,
+        "snapshot {} changed, run with UPDATE_SNAPSHOTS=1 to accept\n\
+         --- expected\n{}\n--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_tokenize() {
+    assert_snapshot("tokenize", &["tokenize", "examples/fibonacci.lox"], None);
+}
+
+#[test]
+fn test_tokenize_error() {
+    assert_snapshot(
+        "tokenize-error",
+        &["tokenize", "tests/fixtures/scan-error.lox"],
+        None,
+    );
+}
+
+#[test]
+fn test_ast() {
+    assert_snapshot("ast", &["ast", "examples/fibonacci.lox"], None);
+    assert_snapshot(
+        "ast-rpn",
+        &["ast", "--format=rpn", "examples/fibonacci.lox"],
+        None,
+    );
+}
+
+#[test]
+fn test_ast_errors() {
+    assert_snapshot(
+        "ast-errors",
+        &["ast", "tests/fixtures/parse-errors.lox"],
+        None,
+    );
+}
+
+#[test]
+fn test_diagnostics() {
+    assert_snapshot(
+        "scan-error",
+        &["run", "tests/fixtures/scan-error.lox"],
+        None,
+    );
+    assert_snapshot(
+        "parse-error",
+        &["run", "tests/fixtures/parse-error.lox"],
+        None,
+    );
+    assert_snapshot(
+        "runtime-error",
+        &["run", "tests/fixtures/runtime-error.lox"],
+        None,
+    );
+}
+
+#[test]
+fn test_repl() {
+    assert_snapshot(
+        "repl",
+        &["run"],
+        Some(":time\nvar a = 1;\na + 2\nprint a\nb\n\"a\" - 1\n:foo\nprint \"done\";\n\"do\" + \"ne\"\n:env\n"),
+    );
+}
@@ -1,9 +1,22 @@
-import init, {run_wasm} from './pkg/relox.js';
+import init, {run_wasm, example_names, example_source} from './pkg/relox.js';
 await init();
 
 const runButton = document.getElementById("run-button");
 const codeInput = document.getElementById("code-input");
 const outputArea = document.getElementById("output");
+const exampleSelect = document.getElementById("example-select");
+
+for (const name of example_names()) {
+    exampleSelect.add(new Option(name, name));
+}
+
+exampleSelect.addEventListener("change", event => {
+    const source = example_source(exampleSelect.value);
+    if (source !== undefined) {
+        codeInput.value = source;
+    }
+    exampleSelect.value = "";
+});
 
 runButton.addEventListener("click", event => {
     const outputText = run_wasm(codeInput.value);